@@ -2,7 +2,7 @@ use bevy::{input::mouse::MouseMotion, prelude::*};
 
 use crate::{Systems, player::LocalPlayer};
 
-const CAMERA_DISTANCE: f32 = 8.0;
+pub const CAMERA_DISTANCE: f32 = 8.0;
 const CAMERA_HEIGHT: f32 = 2.0;
 
 pub struct CameraPlugin;
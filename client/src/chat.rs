@@ -0,0 +1,198 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    input::{
+        ButtonState,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+use voxel_world::{
+    command::{Chat, MovePlayer},
+    event::{ChatMessage, CommandReply, Kicked},
+    player::PlayerInput,
+};
+
+use crate::{
+    connection::bridge::{FromWorld, WorldBridge},
+    player::LocalPlayer,
+};
+
+/// Messages kept on screen.
+const HISTORY: usize = 10;
+/// Seconds a message stays visible while the chat is closed.
+const FADE_AFTER: f32 = 10.0;
+
+#[derive(Resource, Default)]
+pub struct ChatState {
+    open: bool,
+    draft: String,
+    history: VecDeque<(String, f32)>,
+}
+
+#[derive(Component)]
+struct ChatLog;
+
+#[derive(Component)]
+struct ChatDraft;
+
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChatState>()
+            .add_observer(on_chat_message)
+            .add_observer(on_command_reply)
+            .add_observer(on_kicked)
+            .add_systems(Startup, setup_chat)
+            .add_systems(
+                Update,
+                (type_message.before(crate::toggle_cursor_lock), update_chat).chain(),
+            );
+    }
+}
+
+pub fn is_closed(chat: Res<ChatState>) -> bool {
+    !chat.open
+}
+
+fn setup_chat(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(64.0),
+            left: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(4.0)),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(1.0),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: FontSize::Px(16.0),
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                ChatLog,
+            ));
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: FontSize::Px(16.0),
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 1.0, 0.6)),
+                ChatDraft,
+            ));
+        });
+}
+
+/// Opens the chat on Enter, collects typed text and sends it on the next
+/// Enter. Escape closes the chat without sending.
+fn type_message(
+    mut keys: MessageReader<KeyboardInput>,
+    mut buttons: ResMut<ButtonInput<KeyCode>>,
+    mut chat: ResMut<ChatState>,
+    world: Option<Res<WorldBridge>>,
+    local_player: Option<Single<&mut LocalPlayer>>,
+) {
+    let (Some(world), Some(mut local_player)) = (world, local_player) else {
+        keys.clear();
+        return;
+    };
+
+    for key in keys.read() {
+        if key.state != ButtonState::Pressed {
+            continue;
+        }
+
+        if !chat.open {
+            if key.logical_key == Key::Enter {
+                chat.open = true;
+                // Stop walking while typing; input is not read until the
+                // chat closes.
+                local_player.input = PlayerInput {
+                    look: local_player.input.look,
+                    ..default()
+                };
+                world.send(MovePlayer {
+                    input: local_player.input.clone(),
+                });
+            }
+            continue;
+        }
+
+        match &key.logical_key {
+            Key::Enter => {
+                let text = std::mem::take(&mut chat.draft);
+                if !text.trim().is_empty() {
+                    world.send(Chat { text });
+                }
+                chat.open = false;
+            }
+            Key::Escape => {
+                chat.draft.clear();
+                chat.open = false;
+                // Keep the cursor locked.
+                buttons.clear_just_pressed(KeyCode::Escape);
+            }
+            Key::Backspace => {
+                chat.draft.pop();
+            }
+            Key::Space => chat.draft.push(' '),
+            Key::Character(text) => chat.draft.push_str(text),
+            _ => {}
+        }
+    }
+}
+
+impl ChatState {
+    fn push(&mut self, line: String) {
+        self.history.push_back((line, 0.0));
+        while self.history.len() > HISTORY {
+            self.history.pop_front();
+        }
+    }
+}
+
+fn on_chat_message(on: On<FromWorld<ChatMessage>>, mut chat: ResMut<ChatState>) {
+    let message = on.event();
+    chat.push(format!("<{}> {}", message.name, message.text));
+}
+
+fn on_command_reply(on: On<FromWorld<CommandReply>>, mut chat: ResMut<ChatState>) {
+    for line in on.event().text.lines() {
+        chat.push(line.to_string());
+    }
+}
+
+fn on_kicked(on: On<FromWorld<Kicked>>, mut chat: ResMut<ChatState>) {
+    chat.push(format!("Kicked: {}", on.event().reason));
+}
+
+fn update_chat(
+    time: Res<Time>,
+    mut chat: ResMut<ChatState>,
+    mut log: Single<&mut Text, (With<ChatLog>, Without<ChatDraft>)>,
+    mut draft: Single<&mut Text, (With<ChatDraft>, Without<ChatLog>)>,
+) {
+    for (_, age) in &mut chat.history {
+        *age += time.delta_secs();
+    }
+
+    let open = chat.open;
+    log.0 = chat
+        .history
+        .iter()
+        .filter(|(_, age)| open || *age < FADE_AFTER)
+        .map(|(line, _)| line.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    draft.0 = if open {
+        format!("> {}_", chat.draft)
+    } else {
+        String::new()
+    };
+}
@@ -3,10 +3,11 @@ use std::sync::{Arc, OnceLock};
 use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
 use voxel_world::{
     VoxelWorld, bridge::Bridge, command::WorldCommand, envelope::Envelope, event::WorldEvent,
+    generator::PerlinGenerator, request::Accepted,
 };
 
-pub fn host(name: String) -> anyhow::Result<(u32, Bridge)> {
-    let world = VoxelWorld::new(123);
+pub fn host(name: String, view_distance: i32) -> anyhow::Result<(Accepted, Bridge)> {
+    let world = VoxelWorld::new(PerlinGenerator::new(123));
 
     let (cmd_tx, cmd_rx) = unbounded_channel();
     let (req_tx, req_rx) = unbounded_channel();
@@ -20,10 +21,10 @@ pub fn host(name: String) -> anyhow::Result<(u32, Bridge)> {
     std::thread::spawn(move || world.run(cmd_rx, req_rx, event_tx));
 
     let bridge = Bridge::new(cmd_tx, req_tx, event_rx);
-    let id = bridge.connect(name)?;
-    from.set(id).unwrap();
+    let accepted = bridge.connect(name, view_distance)?;
+    from.set(accepted.id).unwrap();
 
-    Ok((id, bridge))
+    Ok((accepted, bridge))
 }
 
 fn pack(
@@ -21,15 +21,22 @@ impl Plugin for NetworkPlugin {
     }
 }
 
-fn setup_connection(mut commands: Commands, settings: Res<Settings>) {
-    let (id, bridge) = match &settings.addr {
-        Some(addr) => quic::connect(addr.clone(), settings.name.clone()),
-        None => local::host(settings.name.clone()),
+fn setup_connection(mut commands: Commands, mut settings: ResMut<Settings>) {
+    let name = settings.name.clone();
+    let (accepted, bridge) = match &settings.addr {
+        Some(addr) => quic::connect(addr.clone(), name, settings.view_distance),
+        None => local::host(name, settings.view_distance),
     }
     .expect("Failed to start world connection");
+    // The server clamps the same way, so this is the distance it streams.
+    settings.max_view_distance = accepted.max_view_distance;
+    settings.view_distance = settings.view_distance.clamp(1, accepted.max_view_distance);
 
-    let name = settings.name.clone();
-    commands.trigger(player::Connected { id, name });
+    commands.trigger(player::Connected {
+        id: accepted.id,
+        name: settings.name.clone(),
+        spawn: Vec3::from_array(accepted.spawn),
+    });
     commands.insert_resource(WorldBridge(bridge));
 }
 
@@ -38,9 +45,26 @@ fn dispatch_world_events(mut commands: Commands, mut world: ResMut<WorldBridge>)
         match msg {
             WorldEvent::PlayerJoined(e) => commands.trigger(FromWorld(e)),
             WorldEvent::PlayerLeft(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::PlayerDied(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::PlayerRespawned(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::PlayerTeleported(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::PlayerEnteredView(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::PlayerExitedView(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::Kicked(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::GameModeChanged(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::InventoryChanged(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::TimeChanged(e) => commands.trigger(FromWorld(e)),
             WorldEvent::PlayerMoved(e) => commands.trigger(FromWorld(e)),
             WorldEvent::ChunkLoaded(e) => commands.trigger(FromWorld(e)),
             WorldEvent::ChunkUnloaded(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::BiomeChanged(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::EntitySpawned(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::EntityUpdated(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::EntityDespawned(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::ItemPickedUp(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::ChatMessage(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::CommandReply(e) => commands.trigger(FromWorld(e)),
+            WorldEvent::StatsUpdated(e) => commands.trigger(FromWorld(e)),
         }
     }
 }
@@ -15,7 +15,7 @@ use voxel_world::{
     bridge::Bridge,
     command::WorldCommand,
     event::WorldEvent,
-    request::{PendingRequest, WorldRequest},
+    request::{Accepted, PendingRequest, WorldRequest},
 };
 
 use crate::connection::cert::SkipServerVerification;
@@ -25,7 +25,11 @@ const MAX_MSG_SIZE: usize = 1024 * 1024;
 
 static RT: OnceLock<Runtime> = OnceLock::new();
 
-pub fn connect(addr: String, name: String) -> anyhow::Result<(u32, Bridge)> {
+pub fn connect(
+    addr: String,
+    name: String,
+    view_distance: i32,
+) -> anyhow::Result<(Accepted, Bridge)> {
     let addr: SocketAddr = addr.parse()?;
     let rt = RT.get_or_init(|| Runtime::new().unwrap());
 
@@ -46,9 +50,9 @@ pub fn connect(addr: String, name: String) -> anyhow::Result<(u32, Bridge)> {
         Ok::<_, anyhow::Error>(bridge)
     })?;
 
-    let id = bridge.connect(name)?;
+    let accepted = bridge.connect(name, view_distance)?;
 
-    Ok((id, bridge))
+    Ok((accepted, bridge))
 }
 
 fn configure_client() -> anyhow::Result<ClientConfig> {
@@ -81,7 +85,18 @@ async fn send_commands(
 ) -> anyhow::Result<()> {
     while let Some(cmd) = cmd_rx.recv().await {
         let bytes = serialize(&cmd);
-        conn.send_datagram(bytes.into())?;
+        match cmd {
+            // Input goes out every frame, and a lost ack only means deltas
+            // against an older state, so neither has to arrive.
+            WorldCommand::MovePlayer(_) | WorldCommand::AckMovement(_) => {
+                conn.send_datagram(bytes.into())?
+            }
+            _ => {
+                let mut send = conn.open_uni().await?;
+                send.write_all(&bytes).await?;
+                send.finish()?;
+            }
+        }
     }
     Ok(())
 }
@@ -123,8 +138,8 @@ async fn relay_requests(
                 send.finish()?;
 
                 let bytes = recv.read_to_end(MAX_MSG_SIZE).await?;
-                let id: u32 = deserialize(&bytes)?;
-                call.reply(id);
+                let accepted: Accepted = deserialize(&bytes)?;
+                call.reply(accepted);
             }
             PendingRequest::Ping(call) => {
                 let (mut send, mut recv) = conn.open_bi().await?;
@@ -2,8 +2,15 @@ use bevy::{
     diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
     prelude::*,
 };
+use voxel_world::{
+    command::WatchStats,
+    event::{BiomeChanged, GameModeChanged, StatsUpdated},
+};
 
-use crate::player::LocalPlayer;
+use crate::{
+    connection::bridge::{FromWorld, WorldBridge},
+    player::LocalPlayer,
+};
 
 #[derive(Component)]
 struct DebugPanel;
@@ -17,6 +24,15 @@ struct PlayerText;
 #[derive(Component)]
 struct CameraText;
 
+#[derive(Component)]
+struct BiomeText;
+
+#[derive(Component)]
+struct GameModeText;
+
+#[derive(Component)]
+struct ServerText;
+
 #[derive(Bundle)]
 struct DebugTextBundle {
     text: Text,
@@ -50,12 +66,16 @@ impl Plugin for DebugPlugin {
             smoothing_factor: 0.5,
             ..default()
         })
+        .add_observer(on_biome_changed)
+        .add_observer(on_game_mode_changed)
+        .add_observer(on_stats_updated)
         .add_systems(Startup, setup_debug_ui)
         .add_systems(
             Update,
             (debug_performance, debug_player_position, debug_camera_info).run_if(is_debug),
         )
-        .add_systems(Update, toggle_debug);
+        .add_systems(Update, toggle_debug)
+        .add_systems(Update, watch_stats.run_if(resource_exists::<WorldBridge>));
     }
 }
 
@@ -99,6 +119,15 @@ fn setup_debug_ui(mut commands: Commands) {
             parent.spawn((DebugTextBundle::default(), PerformanceText));
             parent.spawn((DebugTextBundle::default(), PlayerText));
             parent.spawn((DebugTextBundle::default(), CameraText));
+            parent.spawn((DebugTextBundle::default(), BiomeText));
+            parent.spawn((
+                DebugTextBundle {
+                    text: Text::new("Mode: Survival"),
+                    ..default()
+                },
+                GameModeText,
+            ));
+            parent.spawn((DebugTextBundle::default(), ServerText));
         });
 }
 
@@ -147,3 +176,60 @@ fn debug_camera_info(
         rotation.0.to_degrees()  // yaw
     );
 }
+
+fn on_biome_changed(on: On<FromWorld<BiomeChanged>>, mut text: Single<&mut Text, With<BiomeText>>) {
+    text.0 = format!("Biome: {:?}", on.event().biome);
+}
+
+fn on_game_mode_changed(
+    on: On<FromWorld<GameModeChanged>>,
+    local_player: Single<&LocalPlayer>,
+    mut text: Single<&mut Text, With<GameModeText>>,
+) {
+    let event = on.event();
+    if event.id == local_player.id {
+        text.0 = format!("Mode: {:?}", event.mode);
+    }
+}
+
+/// Asks the server for tick timing only while the panel is open.
+fn watch_stats(
+    debug_state: Res<DebugState>,
+    world: Res<WorldBridge>,
+    mut sent: Local<Option<bool>>,
+) {
+    if *sent != Some(debug_state.enabled) {
+        *sent = Some(debug_state.enabled);
+        world.send(WatchStats {
+            enabled: debug_state.enabled,
+        });
+    }
+}
+
+fn on_stats_updated(
+    on: On<FromWorld<StatsUpdated>>,
+    mut text: Single<&mut Text, With<ServerText>>,
+) {
+    let report = &on.event().report;
+
+    let mut lines = vec![format!(
+        "Server tick: {:.1}ms avg / {:.1}ms max ({:.0}%)",
+        report.mean_ms,
+        report.max_ms,
+        report.mean_ms / report.budget_ms * 100.0
+    )];
+    if let Some(slowest) = report.slowest() {
+        lines.push(format!(
+            "Slowest: {} {:.1}ms",
+            slowest.phase.name(),
+            slowest.mean_ms
+        ));
+    }
+    if report.overruns > 0 || report.skipped > 0 {
+        lines.push(format!(
+            "Overruns: {}, skipped: {}",
+            report.overruns, report.skipped
+        ));
+    }
+    text.0 = lines.join("\n");
+}
@@ -0,0 +1,148 @@
+use bevy::{input::mouse::MouseWheel, prelude::*};
+use voxel_world::{
+    command::SelectSlot,
+    event::{GameModeChanged, InventoryChanged},
+    inventory::{CREATIVE_PALETTE, HOTBAR_SIZE, Inventory},
+    player::GameMode,
+};
+
+use crate::{
+    Systems,
+    connection::bridge::{FromWorld, WorldBridge},
+    player::LocalPlayer,
+};
+
+const SLOT_KEYS: [KeyCode; HOTBAR_SIZE] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+/// The local player's inventory as last sent by the server.
+#[derive(Resource, Default)]
+pub struct PlayerInventory(pub Inventory);
+
+#[derive(Component)]
+struct HotbarSlot(usize);
+
+pub struct HotbarPlugin;
+
+impl Plugin for HotbarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerInventory>()
+            .add_observer(on_inventory_changed)
+            .add_observer(on_game_mode_changed)
+            .add_systems(Startup, setup_hotbar)
+            .add_systems(Update, select_slot.in_set(Systems::Input))
+            .add_systems(
+                Update,
+                update_hotbar.run_if(resource_changed::<PlayerInventory>),
+            );
+    }
+}
+
+fn setup_hotbar(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            column_gap: Val::Px(4.0),
+            ..default()
+        })
+        .with_children(|parent| {
+            for slot in 0..HOTBAR_SIZE {
+                parent.spawn((
+                    Node {
+                        width: Val::Px(72.0),
+                        height: Val::Px(44.0),
+                        padding: UiRect::all(Val::Px(2.0)),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    BorderColor::all(Color::srgba(1.0, 1.0, 1.0, 0.3)),
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+                    Text::new(""),
+                    TextFont {
+                        font_size: FontSize::Px(12.0),
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    HotbarSlot(slot),
+                ));
+            }
+        });
+}
+
+fn select_slot(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut wheel: MessageReader<MouseWheel>,
+    mut inventory: ResMut<PlayerInventory>,
+    world: Res<WorldBridge>,
+) {
+    let current = inventory.0.selected();
+    let scroll: f32 = wheel.read().map(|event| event.y).sum();
+
+    let slot = if let Some(slot) = SLOT_KEYS.iter().position(|&key| keys.just_pressed(key)) {
+        slot
+    } else if scroll > 0.0 {
+        (current + HOTBAR_SIZE - 1) % HOTBAR_SIZE
+    } else if scroll < 0.0 {
+        (current + 1) % HOTBAR_SIZE
+    } else {
+        return;
+    };
+
+    if slot != current {
+        // Show the selection right away; the server confirms it.
+        inventory.0.select(slot);
+        world.send(SelectSlot { slot: slot as u8 });
+    }
+}
+
+fn on_inventory_changed(
+    on: On<FromWorld<InventoryChanged>>,
+    mut inventory: ResMut<PlayerInventory>,
+) {
+    inventory.0 = on.event().inventory.clone();
+}
+
+/// Redraws the hotbar, which shows the creative palette in empty slots.
+fn on_game_mode_changed(
+    on: On<FromWorld<GameModeChanged>>,
+    local_player: Single<&LocalPlayer>,
+    mut inventory: ResMut<PlayerInventory>,
+) {
+    if on.event().id == local_player.id {
+        inventory.set_changed();
+    }
+}
+
+fn update_hotbar(
+    inventory: Res<PlayerInventory>,
+    local_player: Option<Single<&LocalPlayer>>,
+    mut slots: Query<(&HotbarSlot, &mut Text, &mut BorderColor)>,
+) {
+    let creative = local_player.is_some_and(|player| player.game_mode == GameMode::Creative);
+
+    for (HotbarSlot(slot), mut text, mut border) in &mut slots {
+        text.0 = match inventory.0.hotbar()[*slot] {
+            Some(stack) => format!("{}\n{}", stack.voxel.name(), stack.count),
+            None if creative => CREATIVE_PALETTE[*slot].name().to_string(),
+            None => String::new(),
+        };
+
+        *border = if *slot == inventory.0.selected() {
+            BorderColor::all(Color::WHITE)
+        } else {
+            BorderColor::all(Color::srgba(1.0, 1.0, 1.0, 0.3))
+        };
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use voxel_world::{
+    command::{BreakBlock, PlaceBlock},
+    player::GameMode,
+    terrain::voxel_to_chunk_pos,
+};
+
+use crate::{
+    Systems,
+    camera::CAMERA_DISTANCE,
+    connection::bridge::WorldBridge,
+    hotbar::PlayerInventory,
+    player::LocalPlayer,
+    world::chunk::{ChunkData, ChunkEntities},
+};
+
+pub struct InteractionPlugin;
+
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, edit_blocks.in_set(Systems::Input));
+    }
+}
+
+fn edit_blocks(
+    mouse: Res<ButtonInput<MouseButton>>,
+    camera: Single<&Transform, With<Camera3d>>,
+    local_player: Single<&LocalPlayer>,
+    chunk_entities: Res<ChunkEntities>,
+    chunks: Query<&ChunkData>,
+    inventory: Res<PlayerInventory>,
+    world: Res<WorldBridge>,
+    mut digging: Local<Option<IVec3>>,
+) {
+    let mode = local_player.game_mode;
+    if !mouse.pressed(MouseButton::Left) {
+        *digging = None;
+    }
+
+    let breaking = mouse.pressed(MouseButton::Left);
+    let placing = mouse.just_pressed(MouseButton::Right);
+    if !mode.can_interact() || (!breaking && !placing) {
+        return;
+    }
+
+    let is_solid = |pos: IVec3| {
+        let (chunk, local) = voxel_to_chunk_pos(pos);
+        chunk_entities
+            .0
+            .get(&chunk)
+            .and_then(|&entity| chunks.get(entity).ok())
+            .is_some_and(|ChunkData(data)| data.get(local.to_array()).is_solid())
+    };
+
+    let Some((hit, before)) = raycast(
+        camera.translation,
+        Vec3::from(camera.forward()),
+        CAMERA_DISTANCE + mode.reach(),
+        is_solid,
+    ) else {
+        return;
+    };
+
+    // Survival players dig while the button is held and move on to the
+    // next block when the one they were digging breaks or they look away.
+    let start_digging = match mode {
+        GameMode::Survival => breaking && *digging != Some(hit),
+        _ => mouse.just_pressed(MouseButton::Left),
+    };
+
+    if start_digging {
+        *digging = Some(hit);
+        world.send(BreakBlock {
+            pos: hit.to_array(),
+        });
+    } else if placing && inventory.0.selected_item().is_some() {
+        world.send(PlaceBlock {
+            pos: before.to_array(),
+        });
+    }
+}
+
+/// Steps through the voxels along a ray and returns the first solid one,
+/// together with the voxel the ray came from.
+fn raycast(
+    origin: Vec3,
+    dir: Vec3,
+    max_distance: f32,
+    is_solid: impl Fn(IVec3) -> bool,
+) -> Option<(IVec3, IVec3)> {
+    let mut cell = origin.floor().as_ivec3();
+    let step = dir.signum().as_ivec3();
+    let delta = dir.recip().abs();
+
+    let to_edge = Vec3::select(
+        dir.cmpgt(Vec3::ZERO),
+        cell.as_vec3() + Vec3::ONE - origin,
+        origin - cell.as_vec3(),
+    );
+    let mut t_max = Vec3::select(dir.cmpeq(Vec3::ZERO), Vec3::INFINITY, to_edge * delta);
+
+    let mut previous = cell;
+    let mut t = 0.0;
+    while t <= max_distance {
+        if is_solid(cell) {
+            return Some((cell, previous));
+        }
+        previous = cell;
+
+        let axis = if t_max.x < t_max.y && t_max.x < t_max.z {
+            0
+        } else if t_max.y < t_max.z {
+            1
+        } else {
+            2
+        };
+        t = t_max[axis];
+        cell[axis] += step[axis];
+        t_max[axis] += delta[axis];
+    }
+
+    None
+}
@@ -1,10 +1,14 @@
 mod camera;
+mod chat;
 mod connection;
 mod debug;
+mod hotbar;
+mod interaction;
 mod player;
+mod sky;
 mod world;
 
-use std::{env, f32::consts::PI};
+use std::env;
 
 use bevy::{
     asset::AssetMetaCheck,
@@ -12,10 +16,16 @@ use bevy::{
     window::{CursorGrabMode, CursorOptions, PrimaryWindow},
 };
 
+use voxel_world::terrain::CHUNK_RENDER_DISTANCE;
+
 use camera::CameraPlugin;
+use chat::ChatPlugin;
 use connection::NetworkPlugin;
 use debug::DebugPlugin;
+use hotbar::HotbarPlugin;
+use interaction::InteractionPlugin;
 use player::PlayerPlugin;
+use sky::SkyPlugin;
 use world::WorldPlugin;
 
 #[derive(SystemSet, Debug, Clone, Hash, Eq, PartialEq)]
@@ -32,6 +42,10 @@ enum Systems {
 pub struct Settings {
     addr: Option<String>,
     name: String,
+    /// Chunks around the player to ask the server for.
+    view_distance: i32,
+    /// The server's limit on `view_distance`, known once connected.
+    max_view_distance: i32,
 }
 
 impl Settings {
@@ -51,7 +65,19 @@ impl Settings {
             .cloned()
             .unwrap_or_else(|| "Player".to_string());
 
-        Self { addr, name }
+        let view_distance = args
+            .iter()
+            .position(|a| a == "--view-distance" || a == "-v")
+            .and_then(|i| args.get(i + 1))
+            .and_then(|d| d.parse().ok())
+            .unwrap_or(CHUNK_RENDER_DISTANCE);
+
+        Self {
+            addr,
+            name,
+            view_distance,
+            max_view_distance: view_distance,
+        }
     }
 }
 
@@ -62,7 +88,9 @@ fn main() {
     app.configure_sets(
         Update,
         (
-            Systems::Input.run_if(is_cursor_locked),
+            Systems::Input
+                .run_if(is_cursor_locked)
+                .run_if(chat::is_closed),
             Systems::Movement.after(Systems::Input),
             Systems::PostMovement.after(Systems::Movement),
             Systems::Network.after(Systems::PostMovement),
@@ -85,33 +113,21 @@ fn main() {
                 ..default()
             }),
         CameraPlugin,
+        ChatPlugin,
         DebugPlugin,
+        HotbarPlugin,
+        InteractionPlugin,
         PlayerPlugin,
+        SkyPlugin,
         WorldPlugin,
         NetworkPlugin,
     ))
     .insert_resource(settings)
-    .add_systems(Startup, setup)
     .add_systems(Update, toggle_cursor_lock);
 
     app.run();
 }
 
-fn setup(mut commands: Commands) {
-    commands.spawn((
-        DirectionalLight {
-            illuminance: light_consts::lux::OVERCAST_DAY,
-            shadow_maps_enabled: true,
-            ..default()
-        },
-        Transform {
-            translation: Vec3::new(0.0, 2.0, 0.0),
-            rotation: Quat::from_rotation_x(-PI / 4.),
-            ..default()
-        },
-    ));
-}
-
 fn is_cursor_locked(primary_cursor_options: Single<&CursorOptions, With<PrimaryWindow>>) -> bool {
     primary_cursor_options.grab_mode == CursorGrabMode::Locked
 }
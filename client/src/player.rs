@@ -1,7 +1,13 @@
 use std::collections::{HashMap, VecDeque};
 
 use bevy::prelude::*;
-use voxel_world::{TICK_RATE, command::MovePlayer, event::*, player::PlayerInput};
+use voxel_world::{
+    TICK_RATE,
+    command::{AckMovement, MovePlayer, SetMovementMode},
+    event::*,
+    movement::MotionHistory,
+    player::{GameMode, MovementMode, PlayerInput},
+};
 
 use crate::{
     Systems,
@@ -13,10 +19,10 @@ const MAX_DRIFT_TICKS: f64 = 8.0;
 const RESYNC_RATE: f64 = 2.0;
 
 #[derive(Clone, Copy)]
-struct Snapshot {
-    tick: u64,
-    pos: Vec3,
-    _look: Vec3,
+pub struct Snapshot {
+    pub tick: u64,
+    pub pos: Vec3,
+    pub _look: Vec3,
 }
 
 #[derive(Component, Default)]
@@ -25,7 +31,7 @@ pub struct SnapshotBuffer {
 }
 
 impl SnapshotBuffer {
-    fn push(&mut self, snap: Snapshot) {
+    pub fn push(&mut self, snap: Snapshot) {
         if self.snapshots.back().is_some_and(|b| snap.tick <= b.tick) {
             return;
         }
@@ -35,6 +41,10 @@ impl SnapshotBuffer {
         }
     }
 
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
     fn sample(&self, t: f64) -> Option<(Snapshot, Snapshot, f32)> {
         let s = &self.snapshots;
         for i in 0..s.len().saturating_sub(1) {
@@ -49,7 +59,10 @@ impl SnapshotBuffer {
                 return Some((a, b, alpha as f32));
             }
         }
-        None
+        // Players standing still are not resent, so stay on the last one.
+        s.back()
+            .filter(|last| t > last.tick as f64)
+            .map(|&last| (last, last, 0.0))
     }
 }
 
@@ -66,6 +79,8 @@ pub struct LocalPlayer {
     pub id: u32,
     pub name: String,
     pub input: PlayerInput,
+    pub game_mode: GameMode,
+    pub movement: MovementMode,
 }
 
 #[allow(dead_code)]
@@ -79,24 +94,40 @@ pub struct RemotePlayer {
 pub struct Connected {
     pub id: u32,
     pub name: String,
+    pub spawn: Vec3,
 }
 
 #[derive(Default, Resource)]
 pub struct PlayerEntities(pub HashMap<u32, Entity>);
 
+/// Received movement per player, for decoding deltas, and the newest tick
+/// per player not yet acknowledged to the server.
+#[derive(Default, Resource)]
+struct MovementState {
+    histories: HashMap<u32, MotionHistory>,
+    unacked: HashMap<u32, u64>,
+}
+
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<PlayerEntities>()
             .init_resource::<RenderClock>()
+            .init_resource::<MovementState>()
             .add_observer(on_player_joined)
             .add_observer(on_player_left)
             .add_observer(on_position_update)
+            .add_observer(on_player_died)
+            .add_observer(on_player_respawned)
+            .add_observer(on_player_teleported)
+            .add_observer(on_player_entered_view)
+            .add_observer(on_player_exited_view)
+            .add_observer(on_game_mode_changed)
             .add_observer(on_connected)
             .add_systems(
                 Update,
-                (read_input, send_input)
+                (toggle_fly, read_input, send_input, ack_movement)
                     .chain()
                     .in_set(Systems::Input)
                     .run_if(has_local_player),
@@ -126,8 +157,10 @@ fn on_connected(
             id: event.id,
             name: event.name.clone(),
             input: PlayerInput::default(),
+            game_mode: GameMode::default(),
+            movement: MovementMode::default(),
         },
-        Transform::from_xyz(0.0, 60.0, 0.0),
+        Transform::from_translation(event.spawn),
         Mesh3d(meshes.add(Capsule3d::default())),
         MeshMaterial3d(materials.add(Color::srgb(0.3, 0.5, 0.9))),
         SnapshotBuffer::default(),
@@ -161,6 +194,8 @@ fn on_player_joined(
             Mesh3d(meshes.add(Capsule3d::default())),
             MeshMaterial3d(materials.add(Color::WHITE)),
             SnapshotBuffer::default(),
+            // Shown once the server says they are in view.
+            Visibility::Hidden,
         ))
         .id();
 
@@ -171,9 +206,12 @@ fn on_player_left(
     on: On<FromWorld<PlayerLeft>>,
     mut commands: Commands,
     mut players: ResMut<PlayerEntities>,
+    mut movement: ResMut<MovementState>,
 ) {
     let event = on.event();
     println!("{} left", event.name);
+    movement.histories.remove(&event.id);
+    movement.unacked.remove(&event.id);
 
     if let Some(entity) = players.0.remove(&event.id) {
         commands.entity(entity).despawn();
@@ -186,10 +224,18 @@ fn on_position_update(
     remotes: Res<PlayerEntities>,
     mut buffers: Query<&mut SnapshotBuffer>,
     mut clock: ResMut<RenderClock>,
+    mut movement: ResMut<MovementState>,
 ) {
     let event = on.event();
     let (local_entity, local_player) = local.into_inner();
 
+    let history = movement.histories.entry(event.id).or_default();
+    let Some(state) = history.receive(event.tick, event.motion) else {
+        return;
+    };
+    let newest = movement.unacked.entry(event.id).or_default();
+    *newest = (*newest).max(event.tick);
+
     let entity = if event.id == local_player.id {
         local_entity
     } else if let Some(&e) = remotes.0.get(&event.id) {
@@ -201,15 +247,139 @@ fn on_position_update(
     if let Ok(mut buffer) = buffers.get_mut(entity) {
         buffer.push(Snapshot {
             tick: event.tick,
-            pos: Vec3::from_array(event.pos),
-            _look: Vec3::from_array(event.look),
+            pos: state.pos(),
+            _look: state.look(),
         });
     }
 
     clock.latest_tick = clock.latest_tick.max(event.tick);
 }
 
-fn read_input(keyboard: Res<ButtonInput<KeyCode>>, mut local_player: Single<&mut LocalPlayer>) {
+fn on_player_died(on: On<FromWorld<PlayerDied>>) {
+    println!("{} fell out of the world", on.event().name);
+}
+
+fn on_player_respawned(
+    on: On<FromWorld<PlayerRespawned>>,
+    local: Single<(Entity, &LocalPlayer)>,
+    remotes: Res<PlayerEntities>,
+    players: Query<(&mut SnapshotBuffer, &mut Transform)>,
+) {
+    let event = on.event();
+    snap_player(event.id, event.pos, local, remotes, players);
+}
+
+fn on_player_entered_view(
+    on: On<FromWorld<PlayerEnteredView>>,
+    local: Single<(Entity, &LocalPlayer)>,
+    remotes: Res<PlayerEntities>,
+    players: Query<(&mut SnapshotBuffer, &mut Transform)>,
+    mut visibility: Query<&mut Visibility>,
+) {
+    let event = on.event();
+
+    if let Some(&entity) = remotes.0.get(&event.id)
+        && let Ok(mut visibility) = visibility.get_mut(entity)
+    {
+        *visibility = Visibility::Inherited;
+    }
+    snap_player(event.id, event.pos, local, remotes, players);
+}
+
+fn on_player_exited_view(
+    on: On<FromWorld<PlayerExitedView>>,
+    remotes: Res<PlayerEntities>,
+    mut players: Query<(&mut SnapshotBuffer, &mut Visibility)>,
+    mut movement: ResMut<MovementState>,
+) {
+    let event = on.event();
+    // The server starts over with a keyframe if they come back.
+    movement.histories.remove(&event.id);
+    movement.unacked.remove(&event.id);
+
+    if let Some(&entity) = remotes.0.get(&event.id)
+        && let Ok((mut buffer, mut visibility)) = players.get_mut(entity)
+    {
+        buffer.clear();
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn on_player_teleported(
+    on: On<FromWorld<PlayerTeleported>>,
+    local: Single<(Entity, &LocalPlayer)>,
+    remotes: Res<PlayerEntities>,
+    players: Query<(&mut SnapshotBuffer, &mut Transform)>,
+) {
+    let event = on.event();
+    snap_player(event.id, event.pos, local, remotes, players);
+}
+
+/// Puts a player straight at `pos` instead of interpolating there.
+fn snap_player(
+    id: u32,
+    pos: [f32; 3],
+    local: Single<(Entity, &LocalPlayer)>,
+    remotes: Res<PlayerEntities>,
+    mut players: Query<(&mut SnapshotBuffer, &mut Transform)>,
+) {
+    let (local_entity, local_player) = local.into_inner();
+
+    let entity = if id == local_player.id {
+        local_entity
+    } else if let Some(&e) = remotes.0.get(&id) {
+        e
+    } else {
+        return;
+    };
+
+    // Drop old snapshots so the player doesn't glide across the world.
+    if let Ok((mut buffer, mut transform)) = players.get_mut(entity) {
+        buffer.clear();
+        transform.translation = Vec3::from_array(pos);
+    }
+}
+
+fn on_game_mode_changed(
+    on: On<FromWorld<GameModeChanged>>,
+    mut local_player: Single<&mut LocalPlayer>,
+) {
+    let event = on.event();
+
+    if event.id == local_player.id {
+        local_player.game_mode = event.mode;
+        // The server starts creative players flying and drops the others
+        // back to walking.
+        local_player.movement = match event.mode {
+            GameMode::Creative => MovementMode::Fly,
+            _ => MovementMode::Walk,
+        };
+    }
+}
+
+fn toggle_fly(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    world: Res<WorldBridge>,
+    mut local_player: Single<&mut LocalPlayer>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyF) || local_player.game_mode != GameMode::Creative {
+        return;
+    }
+
+    local_player.movement = match local_player.movement {
+        MovementMode::Walk => MovementMode::Fly,
+        MovementMode::Fly => MovementMode::Walk,
+    };
+    world.send(SetMovementMode {
+        mode: local_player.movement,
+    });
+}
+
+fn read_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut local_player: Single<&mut LocalPlayer>,
+) {
     let mut input_dir = Vec3::ZERO;
     let sprint = keyboard.pressed(KeyCode::ShiftLeft);
 
@@ -234,6 +404,7 @@ fn read_input(keyboard: Res<ButtonInput<KeyCode>>, mut local_player: Single<&mut
 
     local_player.input.dir = input_dir.to_array();
     local_player.input.sprint = sprint;
+    local_player.input.dig = mouse.pressed(MouseButton::Left);
 }
 
 pub fn send_input(world: Res<WorldBridge>, local_player: Single<&LocalPlayer>) {
@@ -242,6 +413,17 @@ pub fn send_input(world: Res<WorldBridge>, local_player: Single<&LocalPlayer>) {
     });
 }
 
+/// Tells the server which movement arrived, so it can send deltas against it.
+fn ack_movement(world: Res<WorldBridge>, mut movement: ResMut<MovementState>) {
+    if movement.unacked.is_empty() {
+        return;
+    }
+
+    world.send(AckMovement {
+        acks: movement.unacked.drain().collect(),
+    });
+}
+
 fn advance_render_clock(time: Res<Time>, mut clock: ResMut<RenderClock>) {
     if clock.latest_tick == 0 {
         return;
@@ -0,0 +1,106 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use voxel_world::{TICK_RATE, event::TimeChanged};
+
+use crate::connection::bridge::FromWorld;
+
+const DAY_SKY: Color = Color::srgb(0.47, 0.66, 0.95);
+const DUSK_SKY: Color = Color::srgb(0.93, 0.55, 0.35);
+const NIGHT_SKY: Color = Color::srgb(0.02, 0.03, 0.08);
+/// Light at the darkest point of the night, so terrain stays readable.
+const MOONLIGHT: f32 = light_consts::lux::FULL_MOON_NIGHT * 20.0;
+
+/// The world time, advanced locally between updates from the server.
+#[derive(Resource)]
+pub struct WorldTime {
+    time: f64,
+    day_length: u64,
+    frozen: bool,
+}
+
+impl Default for WorldTime {
+    fn default() -> Self {
+        // Noon until the server says otherwise.
+        Self {
+            time: 0.25,
+            day_length: 1,
+            frozen: true,
+        }
+    }
+}
+
+impl WorldTime {
+    /// How far into the day it is, from 0 at sunrise to 1 at the next.
+    pub fn of_day(&self) -> f32 {
+        (self.time / self.day_length as f64).fract() as f32
+    }
+}
+
+#[derive(Component)]
+struct Sun;
+
+pub struct SkyPlugin;
+
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WorldTime>()
+            .insert_resource(ClearColor(DAY_SKY))
+            .add_observer(on_time_changed)
+            .add_systems(Startup, spawn_sun)
+            .add_systems(Update, (advance_time, update_sky).chain());
+    }
+}
+
+fn spawn_sun(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Sun"),
+        DirectionalLight {
+            illuminance: light_consts::lux::OVERCAST_DAY,
+            shadow_maps_enabled: true,
+            ..default()
+        },
+        Transform::default(),
+        Sun,
+    ));
+}
+
+fn on_time_changed(on: On<FromWorld<TimeChanged>>, mut time: ResMut<WorldTime>) {
+    let event = on.event();
+
+    time.time = event.time as f64;
+    time.day_length = event.day_length.max(1);
+    time.frozen = event.frozen;
+}
+
+fn advance_time(clock: Res<Time>, mut time: ResMut<WorldTime>) {
+    if !time.frozen {
+        time.time += clock.delta_secs_f64() * TICK_RATE as f64;
+    }
+}
+
+fn update_sky(
+    time: Res<WorldTime>,
+    mut clear_color: ResMut<ClearColor>,
+    sun: Single<(&mut DirectionalLight, &mut Transform), With<Sun>>,
+) {
+    let (mut light, mut transform) = sun.into_inner();
+
+    // The sun rises in the east at 0, peaks at noon and sets in the west
+    // halfway through the day.
+    let angle = time.of_day() * TAU;
+    let height = angle.sin();
+    let direction = Vec3::new(angle.cos(), height, 0.2).normalize();
+
+    // Below the horizon the light comes from the moon, opposite the sun.
+    let moon = height < 0.0;
+    transform.look_to(if moon { direction } else { -direction }, Vec3::Y);
+
+    // Fade between night and day around the horizon.
+    let daylight = ((height + 0.1) / 0.3).clamp(0.0, 1.0);
+    light.illuminance = MOONLIGHT + (light_consts::lux::OVERCAST_DAY - MOONLIGHT) * daylight;
+
+    let dusk = 1.0 - (height.abs() / 0.25).min(1.0);
+    let sky = NIGHT_SKY.mix(&DAY_SKY, daylight);
+    clear_color.0 = sky.mix(&DUSK_SKY, dusk * 0.6);
+}
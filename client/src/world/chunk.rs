@@ -5,10 +5,14 @@ use std::{
 
 use bevy::prelude::*;
 use voxel_core::VoxelBuffer;
-use voxel_world::event::*;
+use voxel_world::{
+    command::{AckChunks, SetViewDistance},
+    event::*,
+};
 
 use crate::{
-    connection::bridge::FromWorld,
+    Settings,
+    connection::bridge::{FromWorld, WorldBridge},
     world::{MAX_CHUNK_LOAD_PER_FRAME, NeedsMesh},
 };
 
@@ -24,14 +28,27 @@ pub struct ChunkLoadQueue(pub VecDeque<(IVec3, Arc<VoxelBuffer>)>);
 #[derive(Resource, Default)]
 pub struct ChunkUnloadQueue(pub Vec<IVec3>);
 
-pub fn on_chunk_loaded(on: On<FromWorld<ChunkLoaded>>, mut queue: ResMut<ChunkLoadQueue>) {
+/// Chunks received since the last ack.
+#[derive(Resource, Default)]
+pub struct UnackedChunks(pub u32);
+
+pub fn on_chunk_loaded(
+    on: On<FromWorld<ChunkLoaded>>,
+    mut load_queue: ResMut<ChunkLoadQueue>,
+    mut unload_queue: ResMut<ChunkUnloadQueue>,
+    mut unacked: ResMut<UnackedChunks>,
+) {
     let event = on.event();
+    let pos = IVec3::from_array(event.pos);
+    unacked.0 += 1;
+
+    // The server resends chunks that change, which can leave one empty.
     if event.data.is_all_empty() {
+        load_queue.0.retain(|(p, _)| *p != pos);
+        unload_queue.0.push(pos);
         return;
     }
-    queue
-        .0
-        .push_back((IVec3::from_array(event.pos), event.data.clone()));
+    load_queue.0.push_back((pos, event.data.clone()));
 }
 
 pub fn on_chunk_unloaded(
@@ -45,6 +62,43 @@ pub fn on_chunk_unloaded(
     unload_queue.0.push(pos);
 }
 
+/// Tells the server how many chunks arrived, so it keeps streaming more.
+pub fn ack_chunks(world: Res<WorldBridge>, mut unacked: ResMut<UnackedChunks>) {
+    if unacked.0 == 0 {
+        return;
+    }
+
+    world.send(AckChunks {
+        count: std::mem::take(&mut unacked.0),
+    });
+}
+
+/// Page Up and Page Down change the view distance, within what the server
+/// allows.
+pub fn change_view_distance(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    world: Res<WorldBridge>,
+) {
+    let step = if keys.just_pressed(KeyCode::PageUp) {
+        1
+    } else if keys.just_pressed(KeyCode::PageDown) {
+        -1
+    } else {
+        return;
+    };
+
+    let distance = (settings.view_distance + step).clamp(1, settings.max_view_distance);
+    if distance == settings.view_distance {
+        return;
+    }
+
+    settings.view_distance = distance;
+    world.send(SetViewDistance {
+        distance: settings.view_distance,
+    });
+}
+
 pub fn process_chunk_load_queue(
     mut commands: Commands,
     mut chunk_load_queue: ResMut<ChunkLoadQueue>,
@@ -55,6 +109,13 @@ pub fn process_chunk_load_queue(
             break;
         };
 
+        // Remesh updated chunks in place so the old mesh stays visible until
+        // the new one is ready.
+        if let Some(&entity) = chunk_entities.0.get(&pos) {
+            commands.entity(entity).insert((ChunkData(data), NeedsMesh));
+            continue;
+        }
+
         let world_pos = pos.as_vec3() * UVec3::from_array(data.size).as_vec3();
 
         let entity = commands
@@ -65,9 +126,7 @@ pub fn process_chunk_load_queue(
             ))
             .id();
 
-        if let Some(old) = chunk_entities.0.insert(pos, entity) {
-            commands.entity(old).despawn();
-        }
+        chunk_entities.0.insert(pos, entity);
     }
 }
 
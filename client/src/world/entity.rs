@@ -0,0 +1,227 @@
+use std::{collections::HashMap, sync::Arc};
+
+use bevy::prelude::*;
+use voxel_core::VoxelBuffer;
+use voxel_world::{
+    entity::{self as world_entity, EntityId, EntityKind},
+    event::*,
+};
+
+use crate::{
+    connection::bridge::FromWorld,
+    player::{LocalPlayer, PlayerEntities, Snapshot, SnapshotBuffer},
+    world::mesh::{BlockMaterial, generate::generate_mesh},
+};
+
+/// Size of a dropped item compared to a full block.
+const ITEM_SCALE: f32 = 0.25;
+/// Radians per second a dropped item turns.
+const ITEM_SPIN: f32 = 1.5;
+/// Seconds a picked up item takes to reach the player.
+const PICKUP_TIME: f32 = 0.15;
+
+#[derive(Default, Resource)]
+pub struct WorldEntities(pub HashMap<EntityId, Entity>);
+
+#[derive(Component)]
+pub struct Spin;
+
+/// An item flying into the player that picked it up.
+#[derive(Component)]
+pub struct PickedUp {
+    target: Entity,
+    from: Vec3,
+    elapsed: f32,
+}
+
+pub fn on_entity_spawned(
+    on: On<FromWorld<EntitySpawned>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    block_material: Res<BlockMaterial>,
+    mut entities: ResMut<WorldEntities>,
+) {
+    let event = on.event();
+
+    let mut kind = None;
+    let mut pos = Vec3::ZERO;
+    for component in &event.components {
+        match component {
+            world_entity::Component::Kind(k) => kind = Some(*k),
+            world_entity::Component::Transform(t) => pos = Vec3::from_array(t.pos),
+            _ => {}
+        }
+    }
+    let Some(kind) = kind else {
+        return;
+    };
+
+    let entity = commands
+        .spawn((
+            Name::new(format!("Entity_{}", event.id)),
+            Transform::from_translation(pos),
+            Visibility::default(),
+            SnapshotBuffer::default(),
+        ))
+        .id();
+    spawn_visual(&mut commands, entity, kind, &mut meshes, &block_material);
+
+    // A despawn may have been missed if the entity left view and came back.
+    if let Some(old) = entities.0.insert(event.id, entity) {
+        commands.entity(old).despawn();
+    }
+}
+
+pub fn on_entity_updated(
+    on: On<FromWorld<EntityUpdated>>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    block_material: Res<BlockMaterial>,
+    entities: Res<WorldEntities>,
+    mut buffers: Query<&mut SnapshotBuffer>,
+) {
+    let event = on.event();
+    let Some(&entity) = entities.0.get(&event.id) else {
+        return;
+    };
+
+    for component in &event.components {
+        match component {
+            world_entity::Component::Transform(t) => {
+                if let Ok(mut buffer) = buffers.get_mut(entity) {
+                    buffer.push(Snapshot {
+                        tick: event.tick,
+                        pos: Vec3::from_array(t.pos),
+                        _look: Vec3::ZERO,
+                    });
+                }
+            }
+            world_entity::Component::Kind(kind) => {
+                commands.entity(entity).despawn_related::<Children>();
+                spawn_visual(&mut commands, entity, *kind, &mut meshes, &block_material);
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn on_entity_despawned(
+    on: On<FromWorld<EntityDespawned>>,
+    mut commands: Commands,
+    mut entities: ResMut<WorldEntities>,
+) {
+    if let Some(entity) = entities.0.remove(&on.event().id) {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Attaches what an entity of `kind` looks like to `parent`.
+fn spawn_visual(
+    commands: &mut Commands,
+    parent: Entity,
+    kind: EntityKind,
+    meshes: &mut Assets<Mesh>,
+    block_material: &BlockMaterial,
+) {
+    match kind {
+        EntityKind::FallingBlock { voxel } => {
+            let mut buffer = VoxelBuffer::new([1, 1, 1]);
+            buffer.set([0, 0, 0], voxel);
+            let Some(mesh) = generate_mesh(Arc::new(buffer)) else {
+                return;
+            };
+
+            // The server tracks the block's center, the mesh starts at its
+            // corner.
+            commands.entity(parent).with_child((
+                Transform::from_translation(Vec3::splat(-0.5)),
+                Mesh3d(meshes.add(mesh)),
+                MeshMaterial3d(block_material.0.clone()),
+            ));
+        }
+        EntityKind::Item { stack } => {
+            let mut buffer = VoxelBuffer::new([1, 1, 1]);
+            buffer.set([0, 0, 0], stack.voxel);
+            let Some(mesh) = generate_mesh(Arc::new(buffer)) else {
+                return;
+            };
+
+            commands.entity(parent).with_child((
+                Transform::default(),
+                Visibility::default(),
+                Spin,
+                children![(
+                    Transform::from_translation(Vec3::splat(-0.5 * ITEM_SCALE))
+                        .with_scale(Vec3::splat(ITEM_SCALE)),
+                    Mesh3d(meshes.add(mesh)),
+                    MeshMaterial3d(block_material.0.clone()),
+                )],
+            ));
+        }
+    }
+}
+
+pub fn on_item_picked_up(
+    on: On<FromWorld<ItemPickedUp>>,
+    mut commands: Commands,
+    mut entities: ResMut<WorldEntities>,
+    local: Single<(Entity, &LocalPlayer)>,
+    remotes: Res<PlayerEntities>,
+    transforms: Query<&Transform>,
+) {
+    let event = on.event();
+    let (local_entity, local_player) = local.into_inner();
+
+    let target = if event.player == local_player.id {
+        Some(local_entity)
+    } else {
+        remotes.0.get(&event.player).copied()
+    };
+
+    // Take the item out of the map so the despawn that follows leaves it
+    // alone until it has reached the player.
+    let Some(entity) = entities.0.remove(&event.id) else {
+        return;
+    };
+    let (Some(target), Ok(transform)) = (target, transforms.get(entity)) else {
+        commands.entity(entity).despawn();
+        return;
+    };
+
+    commands
+        .entity(entity)
+        .remove::<SnapshotBuffer>()
+        .insert(PickedUp {
+            target,
+            from: transform.translation,
+            elapsed: 0.0,
+        });
+}
+
+pub fn spin_items(time: Res<Time>, mut items: Query<&mut Transform, With<Spin>>) {
+    for mut transform in &mut items {
+        transform.rotate_y(ITEM_SPIN * time.delta_secs());
+    }
+}
+
+pub fn animate_pickups(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut items: Query<(Entity, &mut PickedUp)>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (entity, mut pickup) in &mut items {
+        pickup.elapsed += time.delta_secs();
+
+        let target = transforms.get(pickup.target).map(|t| t.translation);
+        let t = pickup.elapsed / PICKUP_TIME;
+        let (Ok(target), true) = (target, t < 1.0) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+
+        if let Ok(mut transform) = transforms.get_mut(entity) {
+            transform.translation = pickup.from.lerp(target, t);
+        }
+    }
+}
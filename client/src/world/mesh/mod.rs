@@ -1,4 +1,4 @@
-mod generate;
+pub mod generate;
 
 use bevy::{
     prelude::*,
@@ -19,7 +19,7 @@ pub struct MeshTask(Task<Option<Mesh>>);
 pub struct MeshReady(Option<Mesh>);
 
 #[derive(Resource)]
-pub struct BlockMaterial(Handle<StandardMaterial>);
+pub struct BlockMaterial(pub Handle<StandardMaterial>);
 
 pub fn load_assets(
     asset_server: Res<AssetServer>,
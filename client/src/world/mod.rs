@@ -1,11 +1,13 @@
 pub mod chunk;
+pub mod entity;
 pub mod mesh;
 
 use bevy::prelude::*;
 
 use crate::{
     Systems,
-    world::{chunk::*, mesh::*},
+    connection::bridge::WorldBridge,
+    world::{chunk::*, entity::*, mesh::*},
 };
 
 pub const MAX_CHUNK_LOAD_PER_FRAME: usize = 20;
@@ -19,9 +21,20 @@ impl Plugin for WorldPlugin {
         app.init_resource::<ChunkEntities>()
             .init_resource::<ChunkLoadQueue>()
             .init_resource::<ChunkUnloadQueue>()
+            .init_resource::<UnackedChunks>()
+            .init_resource::<WorldEntities>()
             .add_observer(on_chunk_loaded)
             .add_observer(on_chunk_unloaded)
+            .add_observer(on_entity_spawned)
+            .add_observer(on_entity_updated)
+            .add_observer(on_entity_despawned)
+            .add_observer(on_item_picked_up)
             .add_systems(Startup, load_assets)
+            .add_systems(Update, (spin_items, animate_pickups))
+            .add_systems(
+                Update,
+                (ack_chunks, change_view_distance).run_if(resource_exists::<WorldBridge>),
+            )
             .add_systems(
                 Update,
                 (process_chunk_unload_queue, process_chunk_load_queue)
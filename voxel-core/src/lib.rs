@@ -5,14 +5,112 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Debug)]
 pub struct Voxel(pub u16);
 
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Fluid {
+    Water,
+    Lava,
+}
+
 impl Voxel {
     pub const EMPTY: Self = Self(0);
     pub const DIRT: Self = Self(1);
     pub const STONE: Self = Self(2);
+    pub const GRASS: Self = Self(3);
+    pub const SAND: Self = Self(4);
+    pub const SNOW: Self = Self(5);
+    pub const CACTUS: Self = Self(6);
+    pub const LEAVES: Self = Self(7);
+    pub const COAL_ORE: Self = Self(8);
+    pub const IRON_ORE: Self = Self(9);
+    pub const GOLD_ORE: Self = Self(10);
+    pub const DIAMOND_ORE: Self = Self(11);
+    pub const LOG: Self = Self(12);
+    pub const PLANKS: Self = Self(13);
+    pub const GRAVEL: Self = Self(14);
+
+    /// Fluids keep their kind in the high byte and their level in the low
+    /// byte. The highest level is a source block.
+    pub const FLUID_LEVELS: u8 = 8;
+    pub const WATER: Self = Self::fluid(Fluid::Water, Self::FLUID_LEVELS);
+    pub const LAVA: Self = Self::fluid(Fluid::Lava, Self::FLUID_LEVELS);
+
+    pub const fn fluid(fluid: Fluid, level: u8) -> Self {
+        let kind = match fluid {
+            Fluid::Water => 1,
+            Fluid::Lava => 2,
+        };
+        Self(kind << 8 | level as u16)
+    }
+
+    pub fn as_fluid(&self) -> Option<(Fluid, u8)> {
+        let level = (self.0 & 0xff) as u8;
+        match self.0 >> 8 {
+            1 => Some((Fluid::Water, level)),
+            2 => Some((Fluid::Lava, level)),
+            _ => None,
+        }
+    }
 
     pub fn is_empty(&self) -> bool {
         *self == Self::EMPTY
     }
+
+    pub fn is_fluid(&self) -> bool {
+        self.as_fluid().is_some()
+    }
+
+    pub fn is_solid(&self) -> bool {
+        !self.is_empty() && !self.is_fluid()
+    }
+
+    /// Blocks that fall when nothing solid is under them.
+    pub fn has_gravity(&self) -> bool {
+        matches!(*self, Self::SAND | Self::GRAVEL)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Self::EMPTY => "empty",
+            Self::DIRT => "dirt",
+            Self::STONE => "stone",
+            Self::GRASS => "grass",
+            Self::SAND => "sand",
+            Self::SNOW => "snow",
+            Self::CACTUS => "cactus",
+            Self::LEAVES => "leaves",
+            Self::COAL_ORE => "coal_ore",
+            Self::IRON_ORE => "iron_ore",
+            Self::GOLD_ORE => "gold_ore",
+            Self::DIAMOND_ORE => "diamond_ore",
+            Self::LOG => "log",
+            Self::PLANKS => "planks",
+            Self::GRAVEL => "gravel",
+            _ => match self.as_fluid() {
+                Some((Fluid::Water, _)) => "water",
+                Some((Fluid::Lava, _)) => "lava",
+                None => "unknown",
+            },
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        (0..=Self::GRAVEL.0)
+            .map(Self)
+            .chain([Self::WATER, Self::LAVA])
+            .find(|voxel| voxel.name() == name)
+    }
+
+    /// Seconds of digging it takes to break this voxel by hand.
+    pub fn break_time(&self) -> f32 {
+        match *self {
+            Self::LEAVES | Self::CACTUS => 0.2,
+            Self::DIRT | Self::GRASS | Self::SAND | Self::SNOW | Self::GRAVEL => 0.5,
+            Self::LOG | Self::PLANKS => 1.0,
+            Self::STONE | Self::COAL_ORE => 1.5,
+            Self::IRON_ORE | Self::GOLD_ORE | Self::DIAMOND_ORE => 2.5,
+            _ => 1.0,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -44,6 +142,18 @@ impl VoxelBuffer {
         self.voxels.iter().all(|v| v.is_empty())
     }
 
+    /// A copy with every non-solid voxel cleared.
+    pub fn solids(&self) -> Self {
+        Self {
+            size: self.size,
+            voxels: self
+                .voxels
+                .iter()
+                .map(|v| if v.is_solid() { *v } else { Voxel::EMPTY })
+                .collect(),
+        }
+    }
+
     fn index(&self, pos: [u32; 3]) -> usize {
         (pos[0] + pos[1] * self.size[0] + pos[2] * self.size[0] * self.size[1]) as usize
     }
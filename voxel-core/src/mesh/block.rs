@@ -1,7 +1,7 @@
 use glam::{IVec3, UVec3, Vec3};
 
 use super::{MeshBuffer, Mesher};
-use crate::{Voxel, VoxelBuffer};
+use crate::{Fluid, Voxel, VoxelBuffer};
 
 const ATLAS_SIZE: f32 = 16.0;
 const TEXTURE_SIZE: f32 = 1.0 / ATLAS_SIZE;
@@ -183,7 +183,23 @@ fn get_texture_coords(voxel: Voxel) -> (f32, f32) {
     match voxel {
         Voxel::STONE => (0.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
         Voxel::DIRT => (1.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::GRASS => (2.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::SAND => (3.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::SNOW => (4.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::CACTUS => (5.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::LEAVES => (6.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::COAL_ORE => (7.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::IRON_ORE => (8.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::GOLD_ORE => (9.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::DIAMOND_ORE => (10.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::LOG => (11.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::PLANKS => (12.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+        Voxel::GRAVEL => (15.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
         Voxel::EMPTY => (0.0, 0.0),
-        _ => (15.0 * TEXTURE_SIZE, 15.0 * TEXTURE_SIZE),
+        _ => match voxel.as_fluid() {
+            Some((Fluid::Water, _)) => (13.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+            Some((Fluid::Lava, _)) => (14.0 * TEXTURE_SIZE, 0.0 * TEXTURE_SIZE),
+            None => (15.0 * TEXTURE_SIZE, 15.0 * TEXTURE_SIZE),
+        },
     }
 }
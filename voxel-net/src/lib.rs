@@ -1,4 +1,4 @@
-use std::{collections::HashMap, net::SocketAddr, sync::Arc};
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Instant};
 
 use quinn::{Connection as QuicConnection, Endpoint, ServerConfig};
 use serde::{Serialize, de::DeserializeOwned};
@@ -8,14 +8,36 @@ use tokio::sync::{
 };
 use voxel_world::{
     VoxelWorld,
-    command::WorldCommand,
-    envelope::Envelope,
+    command::{Chat, WorldCommand},
+    entity::Component,
+    envelope::{CONSOLE, Envelope},
     event::WorldEvent,
     request::{Call, Connect, PendingRequest, Ping, WorldRequest},
 };
 
 const MAX_MSG_SIZE: usize = 1024 * 1024; // 1mb
 
+/// Bytes of events sent to one client.
+#[derive(Default)]
+struct Traffic {
+    /// Player movement, which is also counted in `datagrams`.
+    movement: u64,
+    datagrams: u64,
+    streams: u64,
+}
+
+impl Traffic {
+    fn summary(&self, secs: f64) -> String {
+        let rate = |bytes: u64| bytes as f64 / 1024.0 / secs.max(1.0);
+        format!(
+            "{:.1} KB/s datagrams ({:.1} KB/s movement), {:.1} KB/s streams",
+            rate(self.datagrams),
+            rate(self.movement),
+            rate(self.streams),
+        )
+    }
+}
+
 pub struct Server {
     world: VoxelWorld,
     endpoint: Endpoint,
@@ -23,13 +45,17 @@ pub struct Server {
 }
 
 impl Server {
-    pub async fn bind(addr: SocketAddr, config: ServerConfig) -> anyhow::Result<Self> {
+    pub async fn bind(
+        addr: SocketAddr,
+        config: ServerConfig,
+        world: VoxelWorld,
+    ) -> anyhow::Result<Self> {
         let endpoint = Endpoint::server(config, addr)?;
         println!("Listening on {}", addr);
 
         Ok(Self {
             endpoint,
-            world: VoxelWorld::new(123),
+            world,
             clients: Arc::new(RwLock::new(HashMap::new())),
         })
     }
@@ -47,6 +73,9 @@ impl Server {
 
         std::thread::spawn(move || world.run(cmd_rx, req_rx, event_tx));
 
+        let console_tx = cmd_tx.clone();
+        std::thread::spawn(move || console(console_tx));
+
         tokio::select! {
             _ = dispatch(event_rx, clients.clone()) => (),
             _ = accept_connections(endpoint, cmd_tx, req_tx, clients) => ()
@@ -56,11 +85,43 @@ impl Server {
     }
 }
 
+/// Reads slash commands from stdin, one per line. The leading slash is
+/// optional.
+fn console(cmd_tx: UnboundedSender<Envelope<WorldCommand>>) {
+    for line in std::io::stdin().lines() {
+        let Ok(text) = line else {
+            break;
+        };
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        if cmd_tx.send(Envelope::broadcast(Chat { text })).is_err() {
+            break;
+        }
+    }
+}
+
 async fn dispatch(
     mut event_rx: UnboundedReceiver<Envelope<WorldEvent>>,
     clients: Arc<RwLock<HashMap<u32, UnboundedSender<WorldEvent>>>>,
 ) {
     while let Some(envelope) = event_rx.recv().await {
+        if let WorldEvent::ChatMessage(message) = &envelope.payload {
+            println!("<{}> {}", message.name, message.text);
+        }
+
+        if envelope.to == Some(CONSOLE) {
+            if let WorldEvent::CommandReply(reply) = &envelope.payload {
+                if reply.error {
+                    eprintln!("{}", reply.text);
+                } else {
+                    println!("{}", reply.text);
+                }
+            }
+            continue;
+        }
+
         let clients = clients.read().await;
 
         match &envelope.to {
@@ -111,17 +172,24 @@ async fn session(
     let (event_tx, event_rx) = tokio::sync::mpsc::unbounded_channel();
     clients.write().await.insert(id, event_tx);
 
+    let started = Instant::now();
+    let mut traffic = Traffic::default();
+
     tokio::select! {
         _ = receive_commands(connection.clone(), cmd_tx.clone(), id) => (),
         _ = handle_requests(connection.clone(), req_tx.clone()) => (),
-        _ = send_events(connection.clone(), event_rx) => ()
+        _ = send_events(connection.clone(), event_rx, &mut traffic) => ()
     }
 
     cmd_tx.send(Envelope::from(id, WorldCommand::Disconnect))?;
     clients.write().await.remove(&id);
     connection.close(0u32.into(), b"goodbye");
 
-    println!("{name}[{id}] left");
+    let secs = started.elapsed().as_secs_f64();
+    println!(
+        "{name}[{id}] left after {secs:.0}s, sent {}",
+        traffic.summary(secs)
+    );
 
     Ok(())
 }
@@ -136,12 +204,12 @@ async fn handshake(
 
     let (call, rx) = Call::new(connect.clone());
     req_tx.send(PendingRequest::Connect(call))?;
-    let id = rx.await?;
+    let accepted = rx.await?;
 
-    send.write_all(&serialize(&id)).await?;
+    send.write_all(&serialize(&accepted)).await?;
     send.finish()?;
 
-    Ok((id, connect.name))
+    Ok((accepted.id, connect.name))
 }
 
 async fn receive_commands(
@@ -150,7 +218,12 @@ async fn receive_commands(
     id: u32,
 ) -> anyhow::Result<()> {
     loop {
-        let data = connection.read_datagram().await?;
+        // Movement arrives as datagrams; anything that must not be lost
+        // comes over its own stream.
+        let data = tokio::select! {
+            result = connection.read_datagram() => result?.to_vec(),
+            result = connection.accept_uni() => result?.read_to_end(MAX_MSG_SIZE).await?,
+        };
         let cmd: WorldCommand = deserialize(&data)?;
         cmd_tx.send(Envelope::from(id, cmd))?;
     }
@@ -183,15 +256,39 @@ async fn handle_requests(
 async fn send_events(
     connection: QuicConnection,
     mut evt_rx: tokio::sync::mpsc::UnboundedReceiver<WorldEvent>,
+    traffic: &mut Traffic,
 ) -> anyhow::Result<()> {
     while let Some(event) = evt_rx.recv().await {
         match &event {
             WorldEvent::PlayerMoved { .. } => {
                 let bytes = serialize(&event);
+                traffic.movement += bytes.len() as u64;
+                traffic.datagrams += bytes.len() as u64;
+                connection.send_datagram(bytes.into())?;
+            }
+            // Pure motion updates are superseded by the next one anyway.
+            WorldEvent::EntityUpdated(update)
+                if update.components.iter().all(Component::is_motion) =>
+            {
+                let bytes = serialize(&event);
+                traffic.datagrams += bytes.len() as u64;
                 connection.send_datagram(bytes.into())?;
             }
+            // Make sure the player hears why before the session ends.
+            WorldEvent::Kicked(_) => {
+                let bytes = serialize(&event);
+                traffic.streams += bytes.len() as u64;
+                let mut send = connection.open_uni().await?;
+                send.write_all(&bytes).await?;
+                send.finish()?;
+                let _ = send.stopped().await;
+                return Ok(());
+            }
+            // Chat and everything else must arrive, so each gets its own
+            // stream.
             _ => {
                 let bytes = serialize(&event);
+                traffic.streams += bytes.len() as u64;
                 let mut send = connection.open_uni().await?;
                 send.write_all(&bytes).await?;
                 send.finish()?;
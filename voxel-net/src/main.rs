@@ -1,18 +1,56 @@
+use std::fs::File;
+
 use quinn::{ServerConfig, rustls::pki_types::PrivatePkcs8KeyDer};
 
 use voxel_net::Server;
+use voxel_world::{VoxelWorld, generator::PerlinGenerator, replay::Replay};
+
+const SEED: u32 = 123;
+
+/// Path the session is recorded to, if set.
+const RECORD_VAR: &str = "VOXEL_RECORD";
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, path] = args.as_slice()
+        && command == "replay"
+    {
+        return replay(path);
+    }
+
     let addr = "0.0.0.0:8080".parse()?;
     let config = configure_server()?;
 
-    let server = Server::bind(addr, config).await?;
+    let mut world = VoxelWorld::new(PerlinGenerator::new(SEED));
+    if let Ok(path) = std::env::var(RECORD_VAR) {
+        world.record(SEED, File::create(&path)?)?;
+        println!("recording to {path}");
+    }
+
+    let server = Server::bind(addr, config, world).await?;
     server.run().await?;
 
     Ok(())
 }
 
+fn replay(path: &str) -> anyhow::Result<()> {
+    let replay = Replay::read(File::open(path)?)?;
+    let frames = replay.frames.len();
+    let generator = PerlinGenerator::new(replay.header.seed);
+
+    let report = replay.run(generator);
+    match report.divergence {
+        Some(divergence) => println!(
+            "diverged at tick {} after {} of {frames} ticks: expected hash {:016x}, got {:016x}",
+            divergence.tick, report.ticks, divergence.expected, divergence.actual,
+        ),
+        None => println!("replayed {} ticks without divergence", report.ticks),
+    }
+
+    Ok(())
+}
+
 pub fn configure_server() -> anyhow::Result<ServerConfig> {
     let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
     let key = PrivatePkcs8KeyDer::from(cert.signing_key.serialize_der());
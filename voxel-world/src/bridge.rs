@@ -3,7 +3,7 @@ use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 use crate::{
     command::WorldCommand,
     event::WorldEvent,
-    request::{Call, Connect, PendingRequest, Ping, Pong},
+    request::{Accepted, Call, Connect, PendingRequest, Ping, Pong},
 };
 
 pub struct Bridge {
@@ -33,8 +33,11 @@ impl Bridge {
         self.event_rx.try_recv().ok()
     }
 
-    pub fn connect(&self, name: String) -> anyhow::Result<u32> {
-        let (call, rx) = Call::new(Connect { name });
+    pub fn connect(&self, name: String, view_distance: i32) -> anyhow::Result<Accepted> {
+        let (call, rx) = Call::new(Connect {
+            name,
+            view_distance,
+        });
         let _ = self.req_tx.send(PendingRequest::Connect(call));
         Ok(rx.blocking_recv()?)
     }
@@ -0,0 +1,42 @@
+use std::collections::VecDeque;
+
+use crate::config::ChatConfig;
+
+/// Strips control characters and surrounding whitespace and cuts the text
+/// down to the configured length. Returns `None` when nothing is left.
+pub fn sanitize(text: &str, config: &ChatConfig) -> Option<String> {
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(config.max_length)
+        .collect();
+    let text = text.trim();
+
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Ticks on which a player recently chatted.
+#[derive(Default)]
+pub struct ChatLimiter {
+    recent: VecDeque<u64>,
+}
+
+impl ChatLimiter {
+    /// Records a message sent on `tick` if the player is still under the
+    /// limit, and reports whether it may go out.
+    pub fn allow(&mut self, tick: u64, config: &ChatConfig) -> bool {
+        while self
+            .recent
+            .front()
+            .is_some_and(|&sent| tick.saturating_sub(sent) >= config.window)
+        {
+            self.recent.pop_front();
+        }
+
+        if self.recent.len() >= config.max_messages {
+            return false;
+        }
+        self.recent.push_back(tick);
+        true
+    }
+}
@@ -1,15 +1,107 @@
 use serde::{Deserialize, Serialize};
 
-use crate::PlayerInput;
+use crate::{
+    PlayerInput,
+    player::{GameMode, MovementMode},
+};
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct MovePlayer {
     pub input: PlayerInput,
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SetMovementMode {
+    pub mode: MovementMode,
+}
+
+/// Only accepted from the server itself, never from a player.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SetGameMode {
+    pub id: u32,
+    pub mode: GameMode,
+}
+
+/// Starts digging a block. Survival players keep digging while
+/// `PlayerInput::dig` is held; creative players break it at once.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BreakBlock {
+    pub pos: [i32; 3],
+}
+
+/// Places the item in the selected hotbar slot.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlaceBlock {
+    pub pos: [i32; 3],
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SelectSlot {
+    pub slot: u8,
+}
+
+/// Moves up to `count` items onto an empty slot or a stack of the same
+/// item.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MoveItems {
+    pub from: u8,
+    pub to: u8,
+    pub count: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SwapSlots {
+    pub a: u8,
+    pub b: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Chat {
+    pub text: String,
+}
+
+/// Asks for tick timing to be sent once a second, or stops it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WatchStats {
+    pub enabled: bool,
+}
+
+/// The newest movement tick received for each player, as `(id, tick)`.
+/// Lets the world send later movement as deltas against those states.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AckMovement {
+    pub acks: Vec<(u32, u64)>,
+}
+
+/// The view distance, in chunks, the client would like. The world clamps
+/// it to its configured maximum.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SetViewDistance {
+    pub distance: i32,
+}
+
+/// How many chunks arrived since the last ack. Widens the window of chunks
+/// the world streams to this client.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AckChunks {
+    pub count: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 pub enum WorldCommand {
     MovePlayer(MovePlayer),
+    SetMovementMode(SetMovementMode),
+    SetGameMode(SetGameMode),
+    BreakBlock(BreakBlock),
+    PlaceBlock(PlaceBlock),
+    SelectSlot(SelectSlot),
+    MoveItems(MoveItems),
+    SwapSlots(SwapSlots),
+    Chat(Chat),
+    WatchStats(WatchStats),
+    AckMovement(AckMovement),
+    AckChunks(AckChunks),
+    SetViewDistance(SetViewDistance),
     Disconnect,
 }
 
@@ -18,3 +110,75 @@ impl From<MovePlayer> for WorldCommand {
         Self::MovePlayer(cmd)
     }
 }
+
+impl From<SetMovementMode> for WorldCommand {
+    fn from(cmd: SetMovementMode) -> Self {
+        Self::SetMovementMode(cmd)
+    }
+}
+
+impl From<SetGameMode> for WorldCommand {
+    fn from(cmd: SetGameMode) -> Self {
+        Self::SetGameMode(cmd)
+    }
+}
+
+impl From<BreakBlock> for WorldCommand {
+    fn from(cmd: BreakBlock) -> Self {
+        Self::BreakBlock(cmd)
+    }
+}
+
+impl From<PlaceBlock> for WorldCommand {
+    fn from(cmd: PlaceBlock) -> Self {
+        Self::PlaceBlock(cmd)
+    }
+}
+
+impl From<SelectSlot> for WorldCommand {
+    fn from(cmd: SelectSlot) -> Self {
+        Self::SelectSlot(cmd)
+    }
+}
+
+impl From<MoveItems> for WorldCommand {
+    fn from(cmd: MoveItems) -> Self {
+        Self::MoveItems(cmd)
+    }
+}
+
+impl From<SwapSlots> for WorldCommand {
+    fn from(cmd: SwapSlots) -> Self {
+        Self::SwapSlots(cmd)
+    }
+}
+
+impl From<Chat> for WorldCommand {
+    fn from(cmd: Chat) -> Self {
+        Self::Chat(cmd)
+    }
+}
+
+impl From<WatchStats> for WorldCommand {
+    fn from(cmd: WatchStats) -> Self {
+        Self::WatchStats(cmd)
+    }
+}
+
+impl From<AckMovement> for WorldCommand {
+    fn from(cmd: AckMovement) -> Self {
+        Self::AckMovement(cmd)
+    }
+}
+
+impl From<AckChunks> for WorldCommand {
+    fn from(cmd: AckChunks) -> Self {
+        Self::AckChunks(cmd)
+    }
+}
+
+impl From<SetViewDistance> for WorldCommand {
+    fn from(cmd: SetViewDistance) -> Self {
+        Self::SetViewDistance(cmd)
+    }
+}
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{TICK_RATE, terrain::CHUNK_RENDER_DISTANCE};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct WorldConfig {
+    pub fluids: FluidConfig,
+    pub spawn: SpawnConfig,
+    pub players: PlayerConfig,
+    pub items: ItemConfig,
+    pub chat: ChatConfig,
+    pub commands: CommandConfig,
+    pub time: TimeConfig,
+    pub streaming: StreamingConfig,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FluidConfig {
+    /// Ticks between water updates.
+    pub water_interval: u64,
+    /// Ticks between lava updates.
+    pub lava_interval: u64,
+    /// Cap on cells updated in one step; the rest wait for the next one.
+    pub max_updates: usize,
+}
+
+impl Default for FluidConfig {
+    fn default() -> Self {
+        Self {
+            water_interval: 5,
+            lava_interval: 30,
+            max_updates: 4096,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SpawnConfig {
+    /// Column the spawn search starts from, as `[x, z]`.
+    pub origin: [i32; 2],
+    /// How many blocks out from the origin to look for dry ground.
+    pub search_radius: i32,
+}
+
+impl Default for SpawnConfig {
+    fn default() -> Self {
+        Self {
+            origin: [0, 0],
+            search_radius: 128,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PlayerConfig {
+    /// Whether players bump into and push each other.
+    pub collisions: bool,
+    /// Acceleration pushing two overlapping players apart.
+    pub push_strength: f32,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            collisions: true,
+            push_strength: 20.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ItemConfig {
+    /// How close a player has to be to pick up a dropped item.
+    pub pickup_radius: f32,
+    /// Ticks before a dropped item can be picked up.
+    pub pickup_delay: u32,
+    /// Ticks a dropped item lies around before it disappears.
+    pub despawn_after: u32,
+    /// Identical items closer than this merge into one stack.
+    pub merge_radius: f32,
+}
+
+impl Default for ItemConfig {
+    fn default() -> Self {
+        Self {
+            pickup_radius: 1.5,
+            pickup_delay: 20,
+            despawn_after: (300.0 * TICK_RATE) as u32,
+            merge_radius: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChatConfig {
+    /// Longest message in characters; anything past it is cut off.
+    pub max_length: usize,
+    /// Messages a player may send within `window` ticks.
+    pub max_messages: usize,
+    pub window: u64,
+}
+
+impl Default for ChatConfig {
+    fn default() -> Self {
+        Self {
+            max_length: 256,
+            max_messages: 5,
+            window: (5.0 * TICK_RATE) as u64,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CommandConfig {
+    /// Names of players allowed to run operator commands.
+    pub operators: Vec<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TimeConfig {
+    /// Ticks from one sunrise to the next.
+    pub day_length: u64,
+    /// Ticks between time updates sent to every player.
+    pub sync_interval: u64,
+}
+
+impl Default for TimeConfig {
+    fn default() -> Self {
+        Self {
+            day_length: (20.0 * 60.0 * TICK_RATE) as u64,
+            sync_interval: (5.0 * TICK_RATE) as u64,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StreamingConfig {
+    /// Largest view distance, in chunks, a client may ask for.
+    pub max_view_distance: i32,
+    /// Most chunks sent to one player in a tick.
+    pub chunks_per_tick: usize,
+    /// Chunks a new player may have on their way before acking any.
+    pub initial_window: u32,
+    pub min_window: u32,
+    pub max_window: u32,
+    /// Ticks a full window may go without an ack before it is halved.
+    pub stall_ticks: u64,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            max_view_distance: CHUNK_RENDER_DISTANCE,
+            chunks_per_tick: 16,
+            initial_window: 64,
+            min_window: 8,
+            max_window: 1024,
+            stall_ticks: TICK_RATE as u64,
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use glam::Vec3;
+
+use crate::{
+    ACCELERATION, AIR_ACCELERATION, FRICTION, GRAVITY, JUMP_SPEED, SWIM_ACCELERATION,
+    TERMINAL_VELOCITY,
+};
+
+/// Smallest difference between desired and actual movement that counts as
+/// being blocked.
+const EPSILON: f32 = 1e-4;
+
+/// Velocity of a walking player for the next step.
+///
+/// `wish` is the horizontal velocity the input asks for. `medium` is the
+/// buoyancy and drag of the fluid the player is `submerged` in.
+pub fn walk(
+    velocity: Vec3,
+    wish: Vec3,
+    jump: bool,
+    grounded: bool,
+    submerged: f32,
+    medium: (f32, f32),
+    dt: f32,
+) -> Vec3 {
+    let rate = if !grounded {
+        AIR_ACCELERATION
+    } else if wish == Vec3::ZERO {
+        FRICTION
+    } else {
+        ACCELERATION
+    };
+    let mut horizontal = approach(velocity.with_y(0.0), wish, rate * dt);
+    let mut vertical = velocity.y;
+
+    if submerged > 0.0 {
+        let (density, drag) = medium;
+        vertical -= GRAVITY * (1.0 - density * submerged) * dt;
+        if jump {
+            vertical += SWIM_ACCELERATION * dt;
+        }
+
+        let damping = 1.0 / (1.0 + drag * submerged * dt);
+        horizontal *= damping;
+        vertical *= damping;
+    } else if grounded && jump {
+        vertical = JUMP_SPEED;
+    } else if grounded {
+        // Standing on the ground. Pulling down anyway would have the
+        // character controller push back up a little every step.
+        vertical = 0.0;
+    } else {
+        vertical -= GRAVITY * dt;
+    }
+
+    horizontal.with_y(vertical.max(-TERMINAL_VELOCITY))
+}
+
+/// Drops the velocity the character controller could not carry out, so
+/// players stop when they land, bump their head or run into a wall.
+pub fn clip(mut velocity: Vec3, desired: Vec3, moved: Vec3, grounded: bool, dt: f32) -> Vec3 {
+    for axis in [0, 2] {
+        if moved[axis].abs() + EPSILON < desired[axis].abs() {
+            velocity[axis] = moved[axis] / dt;
+        }
+    }
+
+    let landed = grounded && velocity.y < 0.0;
+    let bumped = desired.y > 0.0 && moved.y + EPSILON < desired.y;
+    if landed || bumped {
+        velocity.y = 0.0;
+    }
+
+    velocity
+}
+
+fn approach(current: Vec3, target: Vec3, max_delta: f32) -> Vec3 {
+    let delta = target - current;
+    if delta.length() <= max_delta {
+        target
+    } else {
+        current + delta.normalize() * max_delta
+    }
+}
@@ -0,0 +1,94 @@
+use std::{collections::HashMap, sync::Arc};
+
+use glam::IVec3;
+use voxel_core::{Voxel, VoxelBuffer};
+
+use crate::terrain::Y_RANGE;
+
+/// Decoration edits per chunk, keyed by local position.
+///
+/// Edits only ever fill voxels that are empty in the base terrain, and when
+/// two edits land on the same voxel the one with the higher priority wins.
+/// Both rules are independent of the order chunks are generated in, so the
+/// final terrain is too. For generated chunks only the edits that were
+/// written are kept, which is what tells decorations apart from terrain.
+#[derive(Default)]
+pub struct Decorations {
+    edits: HashMap<IVec3, HashMap<[u32; 3], Voxel>>,
+}
+
+impl Decorations {
+    /// Holds an edit for a chunk that has not been generated yet. Edits for
+    /// chunks outside the world are dropped, as those are never generated.
+    pub fn defer(&mut self, chunk: IVec3, local: [u32; 3], voxel: Voxel) {
+        if !Y_RANGE.contains(&chunk.y) {
+            return;
+        }
+
+        let edits = self.edits.entry(chunk).or_default();
+        match edits.get(&local) {
+            Some(&prev) if priority(prev) >= priority(voxel) => {}
+            _ => {
+                edits.insert(local, voxel);
+            }
+        }
+    }
+
+    /// Applies the edits held for a chunk to its freshly generated terrain.
+    pub fn apply_deferred(&mut self, chunk: IVec3, buffer: &mut VoxelBuffer) {
+        let Some(edits) = self.edits.get_mut(&chunk) else {
+            return;
+        };
+
+        edits.retain(|&local, &mut voxel| {
+            if buffer.get(local).is_empty() {
+                buffer.set(local, voxel);
+                true
+            } else {
+                false
+            }
+        });
+    }
+
+    /// Drops the record of a decorated voxel that was overwritten by
+    /// something else, so it counts as terrain again.
+    pub fn forget(&mut self, chunk: IVec3, local: [u32; 3]) {
+        if let Some(edits) = self.edits.get_mut(&chunk) {
+            edits.remove(&local);
+        }
+    }
+
+    /// Writes an edit into a generated chunk. Returns whether the chunk
+    /// changed.
+    pub fn write(
+        &mut self,
+        chunk: IVec3,
+        local: [u32; 3],
+        voxel: Voxel,
+        buffer: &mut Arc<VoxelBuffer>,
+    ) -> bool {
+        let edits = self.edits.entry(chunk).or_default();
+
+        let writable = match edits.get(&local) {
+            Some(&prev) => priority(voxel) > priority(prev),
+            None => buffer.get(local).is_empty(),
+        };
+        if !writable {
+            return false;
+        }
+
+        edits.insert(local, voxel);
+        Arc::make_mut(buffer).set(local, voxel);
+        true
+    }
+}
+
+/// Foliage gives way to anything more solid; ties are broken by id so the
+/// order stays total.
+fn priority(voxel: Voxel) -> (u8, u16) {
+    let class = match voxel {
+        Voxel::LEAVES => 0,
+        _ => 1,
+    };
+    (class, voxel.0)
+}
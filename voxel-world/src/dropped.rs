@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use glam::Vec3;
+
+use crate::{
+    KILL_HEIGHT,
+    config::ItemConfig,
+    entity::{Entities, EntityId, EntityKind},
+    inventory::{ItemStack, MAX_STACK},
+    physics::Physics,
+    player::PlayerState,
+};
+
+/// Upward speed an item pops out with when it is dropped.
+const POP_SPEED: f32 = 4.0;
+/// Largest sideways speed an item pops out with.
+const SCATTER_SPEED: f32 = 1.5;
+
+struct DroppedItem {
+    stack: ItemStack,
+    age: u32,
+}
+
+/// An item a player collected.
+pub struct Pickup {
+    pub id: EntityId,
+    pub player: u32,
+}
+
+/// Items lying around in the world as physics-driven entities, waiting to
+/// be picked up.
+#[derive(Default)]
+pub struct DroppedItems {
+    items: BTreeMap<EntityId, DroppedItem>,
+}
+
+impl DroppedItems {
+    /// Drops `stack` at `pos`, popping it up and slightly to the side.
+    pub fn spawn(
+        &mut self,
+        physics: &mut Physics,
+        entities: &mut Entities,
+        pos: Vec3,
+        stack: ItemStack,
+    ) -> EntityId {
+        // Scatter by a hash of the position so the same drop always moves
+        // the same way.
+        let hash = pos.to_array().map(f32::to_bits);
+        let hash = hash[0]
+            .wrapping_mul(0x9E37_79B9)
+            .wrapping_add(hash[1].wrapping_mul(0x85EB_CA6B))
+            .wrapping_add(hash[2].wrapping_mul(0xC2B2_AE35));
+        let angle = (hash % 3600) as f32 / 3600.0 * std::f32::consts::TAU;
+        let velocity = Vec3::new(
+            angle.cos() * SCATTER_SPEED,
+            POP_SPEED,
+            angle.sin() * SCATTER_SPEED,
+        );
+
+        let body = physics.add_item_body(pos, velocity);
+        let id = entities.spawn(EntityKind::Item { stack }, pos, Some(body));
+
+        self.items.insert(id, DroppedItem { stack, age: 0 });
+        id
+    }
+
+    /// Ages, merges and hands out items, and returns the ones picked up.
+    pub fn update(
+        &mut self,
+        physics: &mut Physics,
+        entities: &mut Entities,
+        players: &mut BTreeMap<u32, PlayerState>,
+        config: &ItemConfig,
+    ) -> Vec<Pickup> {
+        let mut gone = Vec::new();
+        let mut pickups = Vec::new();
+
+        for (&id, item) in &mut self.items {
+            item.age += 1;
+
+            let pos = entities.get(id).map_or(Vec3::NEG_INFINITY, |e| e.pos());
+            if item.age >= config.despawn_after || pos.y < KILL_HEIGHT {
+                gone.push(id);
+            }
+        }
+        for id in gone.drain(..) {
+            self.remove(physics, entities, id);
+        }
+
+        self.merge(physics, entities, config.merge_radius);
+
+        let mut ids: Vec<_> = players.keys().copied().collect();
+        ids.sort_unstable();
+
+        for (&id, item) in &mut self.items {
+            if item.age < config.pickup_delay {
+                continue;
+            }
+            let Some(pos) = entities.get(id).map(|e| e.pos()) else {
+                continue;
+            };
+
+            for player_id in &ids {
+                let Some(player) = players.get_mut(player_id) else {
+                    continue;
+                };
+                if !player.game_mode.can_interact()
+                    || physics.position(player.body).distance(pos) > config.pickup_radius
+                {
+                    continue;
+                }
+
+                let left = player
+                    .inventory
+                    .add(item.stack.voxel, item.stack.count as u32);
+                if left == 0 {
+                    gone.push(id);
+                    pickups.push(Pickup {
+                        id,
+                        player: *player_id,
+                    });
+                    break;
+                }
+
+                item.stack.count = left as u8;
+                if let Some(entity) = entities.get_mut(id) {
+                    entity.set_kind(EntityKind::Item { stack: item.stack });
+                }
+            }
+        }
+        for id in gone {
+            self.remove(physics, entities, id);
+        }
+
+        pickups
+    }
+
+    /// Folds younger stacks into older identical stacks close by.
+    fn merge(&mut self, physics: &mut Physics, entities: &mut Entities, radius: f32) {
+        let ids: Vec<EntityId> = self.items.keys().copied().collect();
+        let pos = |id| entities.get(id).map(|e| e.pos());
+
+        // Indexed like `ids`, so the stacks merged away are removed in
+        // order.
+        let mut merged = vec![false; ids.len()];
+        for (i, &older) in ids.iter().enumerate() {
+            if merged[i] {
+                continue;
+            }
+            for (j, &younger) in ids.iter().enumerate().skip(i + 1) {
+                if merged[j] {
+                    continue;
+                }
+                let (Some(a), Some(b)) = (pos(older), pos(younger)) else {
+                    continue;
+                };
+                let (target, source) = (self.items[&older].stack, self.items[&younger].stack);
+                if a.distance(b) > radius
+                    || target.voxel != source.voxel
+                    || target.count as u32 + source.count as u32 > MAX_STACK as u32
+                {
+                    continue;
+                }
+
+                let stack = ItemStack {
+                    count: target.count + source.count,
+                    ..target
+                };
+                self.items.get_mut(&older).unwrap().stack = stack;
+                merged[j] = true;
+            }
+        }
+
+        for (&id, _) in ids.iter().zip(merged).filter(|&(_, merged)| merged) {
+            self.remove(physics, entities, id);
+        }
+        for (&id, item) in &self.items {
+            if let Some(entity) = entities.get_mut(id) {
+                entity.set_kind(EntityKind::Item { stack: item.stack });
+            }
+        }
+    }
+
+    fn remove(&mut self, physics: &mut Physics, entities: &mut Entities, id: EntityId) {
+        self.items.remove(&id);
+        if let Some(body) = entities.despawn(id).and_then(|entity| entity.body) {
+            physics.remove_body(body);
+        }
+    }
+}
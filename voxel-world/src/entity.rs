@@ -0,0 +1,219 @@
+use std::collections::{BTreeMap, HashMap};
+
+use glam::Vec3;
+use serde::{Deserialize, Serialize};
+use voxel_core::Voxel;
+
+use crate::{
+    inventory::ItemStack,
+    physics::{BodyHandle, Physics},
+};
+
+pub type EntityId = u32;
+
+/// What an entity is, which decides how clients draw it.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntityKind {
+    FallingBlock { voxel: Voxel },
+    Item { stack: ItemStack },
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct Transform {
+    pub pos: [f32; 3],
+    /// Rotation around the vertical axis, in radians.
+    pub yaw: f32,
+}
+
+/// One replicated piece of an entity's state.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Component {
+    Kind(EntityKind),
+    Transform(Transform),
+    Velocity([f32; 3]),
+    /// Free-form state for kinds that need more than the other components.
+    Data(Vec<u8>),
+}
+
+impl Component {
+    /// Motion goes stale quickly, so losing it in transit is fine.
+    pub fn is_motion(&self) -> bool {
+        matches!(self, Self::Transform(_) | Self::Velocity(_))
+    }
+}
+
+const KIND: u8 = 1 << 0;
+const TRANSFORM: u8 = 1 << 1;
+const VELOCITY: u8 = 1 << 2;
+const DATA: u8 = 1 << 3;
+
+pub struct Entity {
+    kind: EntityKind,
+    transform: Transform,
+    velocity: Vec3,
+    data: Vec<u8>,
+    /// Physics body that drives the transform and velocity. Never
+    /// replicated.
+    pub body: Option<BodyHandle>,
+    changed: u8,
+}
+
+impl Entity {
+    pub fn kind(&self) -> EntityKind {
+        self.kind
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn pos(&self) -> Vec3 {
+        Vec3::from_array(self.transform.pos)
+    }
+
+    pub fn velocity(&self) -> Vec3 {
+        self.velocity
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn set_kind(&mut self, kind: EntityKind) {
+        if self.kind != kind {
+            self.kind = kind;
+            self.changed |= KIND;
+        }
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        if self.transform != transform {
+            self.transform = transform;
+            self.changed |= TRANSFORM;
+        }
+    }
+
+    pub fn set_pos(&mut self, pos: Vec3) {
+        self.set_transform(Transform {
+            pos: pos.to_array(),
+            ..self.transform
+        });
+    }
+
+    pub fn set_velocity(&mut self, velocity: Vec3) {
+        if self.velocity != velocity {
+            self.velocity = velocity;
+            self.changed |= VELOCITY;
+        }
+    }
+
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        if self.data != data {
+            self.data = data;
+            self.changed |= DATA;
+        }
+    }
+
+    /// Every component, for clients that have not seen this entity yet.
+    pub fn components(&self) -> Vec<Component> {
+        self.select(KIND | TRANSFORM | VELOCITY | DATA)
+    }
+
+    fn select(&self, mask: u8) -> Vec<Component> {
+        let mut components = Vec::new();
+        if mask & KIND != 0 {
+            components.push(Component::Kind(self.kind));
+        }
+        if mask & TRANSFORM != 0 {
+            components.push(Component::Transform(self.transform));
+        }
+        if mask & VELOCITY != 0 {
+            components.push(Component::Velocity(self.velocity.to_array()));
+        }
+        if mask & DATA != 0 && !self.data.is_empty() {
+            components.push(Component::Data(self.data.clone()));
+        }
+        components
+    }
+}
+
+/// Everything in the world besides players and terrain.
+///
+/// Ids are never reused. Components changed through the setters are
+/// collected by `take_changes` and sent to the players who can see the
+/// entity.
+#[derive(Default)]
+pub struct Entities {
+    entities: BTreeMap<EntityId, Entity>,
+    despawned: Vec<EntityId>,
+    next_id: EntityId,
+}
+
+impl Entities {
+    pub fn spawn(&mut self, kind: EntityKind, pos: Vec3, body: Option<BodyHandle>) -> EntityId {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.entities.insert(
+            id,
+            Entity {
+                kind,
+                transform: Transform {
+                    pos: pos.to_array(),
+                    yaw: 0.0,
+                },
+                velocity: Vec3::ZERO,
+                data: Vec::new(),
+                body,
+                changed: 0,
+            },
+        );
+        id
+    }
+
+    /// Removes an entity and hands it back so its owner can clean up its
+    /// body.
+    pub fn despawn(&mut self, id: EntityId) -> Option<Entity> {
+        let entity = self.entities.remove(&id)?;
+        self.despawned.push(id);
+        Some(entity)
+    }
+
+    pub fn get(&self, id: EntityId) -> Option<&Entity> {
+        self.entities.get(&id)
+    }
+
+    pub fn get_mut(&mut self, id: EntityId) -> Option<&mut Entity> {
+        self.entities.get_mut(&id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (EntityId, &Entity)> {
+        self.entities.iter().map(|(&id, entity)| (id, entity))
+    }
+
+    /// Copies the motion of every physics-driven entity from its body.
+    pub fn sync_bodies(&mut self, physics: &Physics) {
+        for entity in self.entities.values_mut() {
+            if let Some(body) = entity.body {
+                entity.set_pos(physics.position(body));
+                entity.set_velocity(physics.velocity(body));
+            }
+        }
+    }
+
+    /// Components changed since the last call, by entity.
+    pub fn take_changes(&mut self) -> HashMap<EntityId, Vec<Component>> {
+        self.entities
+            .iter_mut()
+            .filter(|(_, entity)| entity.changed != 0)
+            .map(|(&id, entity)| {
+                let changed = std::mem::take(&mut entity.changed);
+                (id, entity.select(changed))
+            })
+            .collect()
+    }
+
+    pub fn take_despawned(&mut self) -> Vec<EntityId> {
+        std::mem::take(&mut self.despawned)
+    }
+}
@@ -1,3 +1,7 @@
+/// Player ids start at 1, so events sent to this id are meant for the
+/// server console.
+pub const CONSOLE: u32 = 0;
+
 pub struct Envelope<T> {
     pub to: Option<u32>,
     pub from: Option<u32>,
@@ -3,12 +3,20 @@ use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use voxel_core::VoxelBuffer;
 
+use crate::{
+    entity::{Component, EntityId},
+    generator::Biome,
+    inventory::Inventory,
+    movement::Motion,
+    player::GameMode,
+    stats::StatsReport,
+};
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct PlayerMoved {
     pub tick: u64,
     pub id: u32,
-    pub pos: [f32; 3],
-    pub look: [f32; 3],
+    pub motion: Motion,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -23,6 +31,68 @@ pub struct PlayerLeft {
     pub name: String,
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerDied {
+    pub id: u32,
+    pub name: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerRespawned {
+    pub id: u32,
+    pub pos: [f32; 3],
+}
+
+/// Another player came into the receiving player's loaded chunks. Their
+/// movement is sent from now on, until `PlayerExitedView`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerEnteredView {
+    pub id: u32,
+    pub pos: [f32; 3],
+}
+
+/// Another player went out of range or out of sight, e.g. by becoming a
+/// spectator.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerExitedView {
+    pub id: u32,
+}
+
+/// A player was moved somewhere else at once, e.g. by `/tp`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerTeleported {
+    pub id: u32,
+    pub pos: [f32; 3],
+}
+
+/// Sent to a player right before they are disconnected.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Kicked {
+    pub reason: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GameModeChanged {
+    pub id: u32,
+    pub mode: GameMode,
+}
+
+/// The full contents of a player's inventory, sent to its owner.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct InventoryChanged {
+    pub inventory: Inventory,
+}
+
+/// The world time at `tick`. Clients keep the clock running between
+/// updates unless it is frozen.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TimeChanged {
+    pub tick: u64,
+    pub time: u64,
+    pub day_length: u64,
+    pub frozen: bool,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ChunkLoaded {
     pub pos: [i32; 3],
@@ -34,13 +104,86 @@ pub struct ChunkUnloaded {
     pub pos: [i32; 3],
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BiomeChanged {
+    pub biome: Biome,
+}
+
+/// An entity came into view, with all of its components.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EntitySpawned {
+    pub tick: u64,
+    pub id: EntityId,
+    pub components: Vec<Component>,
+}
+
+/// Components of a visible entity that changed this tick.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EntityUpdated {
+    pub tick: u64,
+    pub id: EntityId,
+    pub components: Vec<Component>,
+}
+
+/// An entity was removed or went out of view.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct EntityDespawned {
+    pub id: EntityId,
+}
+
+/// A player collected a dropped item. The item's entity is despawned right
+/// after, so clients can animate it flying to the player.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ItemPickedUp {
+    pub id: EntityId,
+    pub player: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub from: u32,
+    pub name: String,
+    pub text: String,
+    /// World tick the message was sent on.
+    pub timestamp: u64,
+}
+
+/// The outcome of a slash command, for whoever ran it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CommandReply {
+    pub text: String,
+    pub error: bool,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StatsUpdated {
+    pub report: StatsReport,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub enum WorldEvent {
     PlayerMoved(PlayerMoved),
     PlayerJoined(PlayerJoined),
     PlayerLeft(PlayerLeft),
+    PlayerDied(PlayerDied),
+    PlayerRespawned(PlayerRespawned),
+    PlayerTeleported(PlayerTeleported),
+    PlayerEnteredView(PlayerEnteredView),
+    PlayerExitedView(PlayerExitedView),
+    Kicked(Kicked),
+    GameModeChanged(GameModeChanged),
+    InventoryChanged(InventoryChanged),
+    TimeChanged(TimeChanged),
     ChunkLoaded(ChunkLoaded),
     ChunkUnloaded(ChunkUnloaded),
+    BiomeChanged(BiomeChanged),
+    EntitySpawned(EntitySpawned),
+    EntityUpdated(EntityUpdated),
+    EntityDespawned(EntityDespawned),
+    ItemPickedUp(ItemPickedUp),
+    ChatMessage(ChatMessage),
+    CommandReply(CommandReply),
+    StatsUpdated(StatsUpdated),
 }
 
 impl From<PlayerMoved> for WorldEvent {
@@ -61,6 +204,60 @@ impl From<PlayerLeft> for WorldEvent {
     }
 }
 
+impl From<PlayerDied> for WorldEvent {
+    fn from(e: PlayerDied) -> Self {
+        Self::PlayerDied(e)
+    }
+}
+
+impl From<PlayerRespawned> for WorldEvent {
+    fn from(e: PlayerRespawned) -> Self {
+        Self::PlayerRespawned(e)
+    }
+}
+
+impl From<PlayerTeleported> for WorldEvent {
+    fn from(e: PlayerTeleported) -> Self {
+        Self::PlayerTeleported(e)
+    }
+}
+
+impl From<PlayerEnteredView> for WorldEvent {
+    fn from(e: PlayerEnteredView) -> Self {
+        Self::PlayerEnteredView(e)
+    }
+}
+
+impl From<PlayerExitedView> for WorldEvent {
+    fn from(e: PlayerExitedView) -> Self {
+        Self::PlayerExitedView(e)
+    }
+}
+
+impl From<Kicked> for WorldEvent {
+    fn from(e: Kicked) -> Self {
+        Self::Kicked(e)
+    }
+}
+
+impl From<GameModeChanged> for WorldEvent {
+    fn from(e: GameModeChanged) -> Self {
+        Self::GameModeChanged(e)
+    }
+}
+
+impl From<InventoryChanged> for WorldEvent {
+    fn from(e: InventoryChanged) -> Self {
+        Self::InventoryChanged(e)
+    }
+}
+
+impl From<TimeChanged> for WorldEvent {
+    fn from(e: TimeChanged) -> Self {
+        Self::TimeChanged(e)
+    }
+}
+
 impl From<ChunkLoaded> for WorldEvent {
     fn from(e: ChunkLoaded) -> Self {
         Self::ChunkLoaded(e)
@@ -72,3 +269,51 @@ impl From<ChunkUnloaded> for WorldEvent {
         Self::ChunkUnloaded(e)
     }
 }
+
+impl From<BiomeChanged> for WorldEvent {
+    fn from(e: BiomeChanged) -> Self {
+        Self::BiomeChanged(e)
+    }
+}
+
+impl From<EntitySpawned> for WorldEvent {
+    fn from(e: EntitySpawned) -> Self {
+        Self::EntitySpawned(e)
+    }
+}
+
+impl From<EntityUpdated> for WorldEvent {
+    fn from(e: EntityUpdated) -> Self {
+        Self::EntityUpdated(e)
+    }
+}
+
+impl From<EntityDespawned> for WorldEvent {
+    fn from(e: EntityDespawned) -> Self {
+        Self::EntityDespawned(e)
+    }
+}
+
+impl From<ItemPickedUp> for WorldEvent {
+    fn from(e: ItemPickedUp) -> Self {
+        Self::ItemPickedUp(e)
+    }
+}
+
+impl From<ChatMessage> for WorldEvent {
+    fn from(e: ChatMessage) -> Self {
+        Self::ChatMessage(e)
+    }
+}
+
+impl From<CommandReply> for WorldEvent {
+    fn from(e: CommandReply) -> Self {
+        Self::CommandReply(e)
+    }
+}
+
+impl From<StatsUpdated> for WorldEvent {
+    fn from(e: StatsUpdated) -> Self {
+        Self::StatsUpdated(e)
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use glam::{IVec3, Vec3};
+use voxel_core::Voxel;
+
+use crate::{
+    KILL_HEIGHT,
+    entity::{Entities, EntityId, EntityKind},
+    physics::Physics,
+    terrain::Terrain,
+};
+
+/// Ticks a block falls before it is allowed to settle.
+const MIN_AGE: u32 = 2;
+/// Ticks after which a block that never settled is thrown away.
+const MAX_AGE: u32 = 600;
+/// How far above its resting cell a block may be put back when that cell is
+/// taken.
+const MAX_RAISE: i32 = 4;
+
+struct FallingBlock {
+    voxel: Voxel,
+    age: u32,
+}
+
+pub enum Settled {
+    Landed { pos: IVec3 },
+    Lost,
+}
+
+/// Sand, gravel and anything else with gravity that lost the voxel under it,
+/// simulated as physics-driven entities until it comes to rest.
+#[derive(Default)]
+pub struct FallingBlocks {
+    blocks: BTreeMap<EntityId, FallingBlock>,
+}
+
+impl FallingBlocks {
+    /// Lifts `voxel` at `pos` out of the terrain and drops it as an entity.
+    pub fn spawn(
+        &mut self,
+        terrain: &mut Terrain,
+        physics: &mut Physics,
+        entities: &mut Entities,
+        pos: IVec3,
+        voxel: Voxel,
+    ) -> EntityId {
+        terrain.set_voxel(pos, Voxel::EMPTY);
+
+        let center = pos.as_vec3() + Vec3::splat(0.5);
+        let body = physics.add_block_body(center);
+        let id = entities.spawn(EntityKind::FallingBlock { voxel }, center, Some(body));
+
+        self.blocks.insert(id, FallingBlock { voxel, age: 0 });
+        id
+    }
+
+    /// Turns blocks that came to rest back into voxels and despawns them.
+    pub fn settle(
+        &mut self,
+        terrain: &mut Terrain,
+        physics: &mut Physics,
+        entities: &mut Entities,
+    ) -> Vec<Settled> {
+        let mut settled = Vec::new();
+
+        for (&id, block) in &mut self.blocks {
+            block.age += 1;
+
+            let Some(body) = entities.get(id).and_then(|entity| entity.body) else {
+                settled.push((id, Settled::Lost));
+                continue;
+            };
+
+            let center = physics.position(body);
+            let cell = (center - Vec3::splat(0.5)).round().as_ivec3();
+            let resting = block.age >= MIN_AGE
+                && physics.velocity(body).y.abs() < 0.01
+                && terrain.voxel(cell - IVec3::Y).is_some_and(|v| v.is_solid());
+
+            if resting {
+                let free = (0..=MAX_RAISE)
+                    .map(|dy| cell + IVec3::Y * dy)
+                    .find(|&p| terrain.voxel(p).is_some_and(|v| !v.is_solid()));
+
+                settled.push((
+                    id,
+                    match free {
+                        Some(pos) => {
+                            terrain.set_voxel(pos, block.voxel);
+                            Settled::Landed { pos }
+                        }
+                        None => Settled::Lost,
+                    },
+                ));
+            } else if block.age >= MAX_AGE || center.y < KILL_HEIGHT {
+                settled.push((id, Settled::Lost));
+            }
+        }
+
+        settled
+            .into_iter()
+            .map(|(id, settled)| {
+                self.blocks.remove(&id);
+                if let Some(body) = entities.despawn(id).and_then(|entity| entity.body) {
+                    physics.remove_body(body);
+                }
+                settled
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::{HashMap, HashSet};
+
+use glam::{IVec3, Vec3};
+use voxel_core::{Fluid, Voxel, VoxelBuffer};
+
+use crate::{
+    PLAYER_HALF_HEIGHT,
+    config::FluidConfig,
+    terrain::{CHUNK_SIZE, Terrain},
+};
+
+const SOURCE: u8 = Voxel::FLUID_LEVELS;
+/// Level of fluid falling straight down.
+const FALLING: u8 = SOURCE - 1;
+
+const HORIZONTAL: [IVec3; 4] = [IVec3::X, IVec3::NEG_X, IVec3::Z, IVec3::NEG_Z];
+const NEIGHBORS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+/// Cellular automaton for water and lava.
+///
+/// Only cells that may change are tracked. Each step reads the terrain as it
+/// was at the start of the step and merges conflicting writes by a fixed
+/// priority, so the result never depends on iteration order.
+#[derive(Default)]
+pub struct Fluids {
+    active: HashSet<IVec3>,
+}
+
+impl Fluids {
+    /// Wakes the fluid cells at and around a voxel that just changed.
+    pub fn activate_around(&mut self, terrain: &Terrain, pos: IVec3) {
+        for p in std::iter::once(pos).chain(NEIGHBORS.map(|d| pos + d)) {
+            if terrain.voxel(p).is_some_and(|v| v.is_fluid()) {
+                self.active.insert(p);
+            }
+        }
+    }
+
+    /// Wakes unsettled fluid inside a freshly generated chunk, and fluid in
+    /// the surrounding chunks that borders empty space in it.
+    pub fn activate_chunk(&mut self, terrain: &Terrain, chunk: IVec3, data: &VoxelBuffer) {
+        let size = CHUNK_SIZE.as_ivec3();
+        let origin = chunk * size;
+
+        for x in 0..size.x {
+            for y in 0..size.y {
+                for z in 0..size.z {
+                    let local = IVec3::new(x, y, z);
+                    let world = origin + local;
+                    let voxel = data.get(local.as_uvec3().to_array());
+
+                    if voxel.is_fluid() {
+                        if !update(terrain, world).is_empty() {
+                            self.active.insert(world);
+                        }
+                    } else if voxel.is_empty() {
+                        for d in NEIGHBORS {
+                            let n = local + d;
+                            let outside = n.cmplt(IVec3::ZERO).any() || n.cmpge(size).any();
+                            if outside && terrain.voxel(world + d).is_some_and(|v| v.is_fluid()) {
+                                self.active.insert(world + d);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Advances every fluid that is due on this tick.
+    pub fn step(&mut self, terrain: &mut Terrain, tick: u64, config: &FluidConfig) {
+        let due = |fluid: Fluid| {
+            let interval = match fluid {
+                Fluid::Water => config.water_interval,
+                Fluid::Lava => config.lava_interval,
+            };
+            interval > 0 && tick.is_multiple_of(interval)
+        };
+
+        self.active
+            .retain(|&p| terrain.voxel(p).is_some_and(|v| v.is_fluid()));
+
+        let mut cells: Vec<IVec3> = self
+            .active
+            .iter()
+            .copied()
+            .filter(|&p| {
+                let (fluid, _) = terrain.voxel(p).and_then(|v| v.as_fluid()).unwrap();
+                due(fluid)
+            })
+            .collect();
+        if cells.is_empty() {
+            return;
+        }
+
+        cells.sort_by_key(|p| (p.y, p.x, p.z));
+        cells.truncate(config.max_updates);
+
+        let mut changes: HashMap<IVec3, Voxel> = HashMap::new();
+        for &cell in &cells {
+            self.active.remove(&cell);
+            for (pos, voxel) in update(terrain, cell) {
+                let merged = match changes.get(&pos) {
+                    Some(&prev) => merge(prev, voxel),
+                    None => voxel,
+                };
+                changes.insert(pos, merged);
+            }
+        }
+
+        for (pos, voxel) in changes {
+            if terrain.set_voxel(pos, voxel) {
+                self.activate_around(terrain, pos);
+            }
+        }
+    }
+}
+
+/// The writes one fluid cell wants to make, given the current terrain.
+fn update(terrain: &Terrain, pos: IVec3) -> Vec<(IVec3, Voxel)> {
+    let Some((fluid, level)) = terrain.voxel(pos).and_then(|v| v.as_fluid()) else {
+        return Vec::new();
+    };
+    let at = |offset: IVec3| terrain.voxel(pos + offset);
+    let mut changes = Vec::new();
+
+    // Lava touching water hardens, and water does not flow into it.
+    for d in NEIGHBORS {
+        if let Some((other, _)) = at(d).and_then(|v| v.as_fluid())
+            && other != fluid
+        {
+            let lava = if fluid == Fluid::Lava { pos } else { pos + d };
+            changes.push((lava, Voxel::STONE));
+        }
+    }
+    if fluid == Fluid::Lava && !changes.is_empty() {
+        return changes;
+    }
+
+    if level < SOURCE {
+        let fed = if at(IVec3::Y).and_then(|v| v.as_fluid()).map(|(f, _)| f) == Some(fluid) {
+            FALLING
+        } else {
+            HORIZONTAL
+                .iter()
+                .filter_map(|&d| at(d).and_then(|v| v.as_fluid()))
+                .filter(|&(f, _)| f == fluid)
+                .map(|(_, l)| l.saturating_sub(decay(fluid)))
+                .max()
+                .unwrap_or(0)
+        };
+
+        if fed != level {
+            let voxel = if fed == 0 {
+                Voxel::EMPTY
+            } else {
+                Voxel::fluid(fluid, fed)
+            };
+            changes.push((pos, voxel));
+            return changes;
+        }
+    }
+
+    match at(IVec3::NEG_Y) {
+        Some(below) if below.is_empty() => {
+            changes.push((pos + IVec3::NEG_Y, Voxel::fluid(fluid, FALLING)));
+            return changes;
+        }
+        Some(below) if below.as_fluid().is_some_and(|(f, _)| f == fluid) => return changes,
+        None => return changes,
+        _ => (),
+    }
+
+    let spread = level.saturating_sub(decay(fluid));
+    if spread == 0 {
+        return changes;
+    }
+
+    for d in HORIZONTAL {
+        let flows = match at(d) {
+            Some(v) if v.is_empty() => true,
+            Some(v) => v.as_fluid().is_some_and(|(f, l)| f == fluid && l < spread),
+            None => false,
+        };
+        if flows {
+            changes.push((pos + d, Voxel::fluid(fluid, spread)));
+        }
+    }
+
+    changes
+}
+
+fn decay(fluid: Fluid) -> u8 {
+    match fluid {
+        Fluid::Water => 1,
+        Fluid::Lava => 2,
+    }
+}
+
+/// Resolves two writes to the same cell: solids win, different fluids meet
+/// as stone, otherwise the higher level wins.
+fn merge(a: Voxel, b: Voxel) -> Voxel {
+    if a.is_solid() || b.is_solid() {
+        return Voxel::STONE;
+    }
+
+    match (a.as_fluid(), b.as_fluid()) {
+        (Some((fa, la)), Some((fb, lb))) if fa == fb => Voxel::fluid(fa, la.max(lb)),
+        (Some(_), Some(_)) => Voxel::STONE,
+        (Some(_), None) => a,
+        _ => b,
+    }
+}
+
+/// Buoyancy relative to a player body, and linear damping when fully
+/// submerged.
+pub fn medium(fluid: Fluid) -> (f32, f32) {
+    match fluid {
+        Fluid::Water => (1.2, 3.0),
+        Fluid::Lava => (1.5, 8.0),
+    }
+}
+
+/// How much of a player standing at `pos` is under fluid, from zero to one,
+/// and the `medium` of that fluid.
+pub fn submersion(terrain: &Terrain, pos: Vec3) -> (f32, (f32, f32)) {
+    // Sample the bottom, middle and top of the body.
+    let offsets = [-0.75, 0.0, 0.75].map(|dy| dy * PLAYER_HALF_HEIGHT);
+
+    let mut submerged = 0.0;
+    let mut medium = (0.0, 0.0);
+    for dy in offsets {
+        let voxel_pos = (pos + Vec3::Y * dy).floor().as_ivec3();
+        if let Some((fluid, _)) = terrain.voxel(voxel_pos).and_then(|v| v.as_fluid()) {
+            submerged += 1.0 / offsets.len() as f32;
+            medium = self::medium(fluid);
+        }
+    }
+
+    (submerged, medium)
+}
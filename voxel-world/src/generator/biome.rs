@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use voxel_core::Voxel;
+
+use super::structure::{Structure, StructureRule};
+
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Biome {
+    Plains,
+    Desert,
+    Mountains,
+    Ocean,
+    Forest,
+}
+
+pub const BIOMES: [Biome; 5] = [
+    Biome::Plains,
+    Biome::Desert,
+    Biome::Mountains,
+    Biome::Ocean,
+    Biome::Forest,
+];
+
+/// Heightmap parameters: `base + perlin(pos * frequency) * amplitude`.
+#[derive(Copy, Clone, Debug)]
+pub struct HeightProfile {
+    pub base: f64,
+    pub amplitude: f64,
+    pub frequency: f64,
+}
+
+/// Features stacked on top of a surface column.
+#[derive(Copy, Clone, Debug)]
+pub enum Decoration {
+    None,
+    Column {
+        voxel: Voxel,
+        min_height: i32,
+        max_height: i32,
+        density: f64,
+    },
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct BiomeProfile {
+    /// Ideal `[temperature, humidity]`, both roughly in `-1..1`.
+    pub climate: [f64; 2],
+    pub height: HeightProfile,
+    pub surface: Voxel,
+    pub subsurface: Voxel,
+    pub decoration: Decoration,
+    pub structures: &'static [StructureRule],
+}
+
+impl Biome {
+    pub const fn profile(self) -> BiomeProfile {
+        match self {
+            Biome::Plains => BiomeProfile {
+                climate: [0.2, 0.0],
+                height: HeightProfile {
+                    base: 32.0,
+                    amplitude: 6.0,
+                    frequency: 0.01,
+                },
+                surface: Voxel::GRASS,
+                subsurface: Voxel::DIRT,
+                decoration: Decoration::Column {
+                    voxel: Voxel::LEAVES,
+                    min_height: 1,
+                    max_height: 1,
+                    density: 0.004,
+                },
+                structures: &[
+                    StructureRule {
+                        structure: Structure::Tree,
+                        chance: 0.001,
+                    },
+                    StructureRule {
+                        structure: Structure::Boulder,
+                        chance: 0.0015,
+                    },
+                    StructureRule {
+                        structure: Structure::Hut,
+                        chance: 0.0002,
+                    },
+                ],
+            },
+            Biome::Desert => BiomeProfile {
+                climate: [0.6, -0.5],
+                height: HeightProfile {
+                    base: 34.0,
+                    amplitude: 10.0,
+                    frequency: 0.015,
+                },
+                surface: Voxel::SAND,
+                subsurface: Voxel::SAND,
+                decoration: Decoration::Column {
+                    voxel: Voxel::CACTUS,
+                    min_height: 1,
+                    max_height: 3,
+                    density: 0.006,
+                },
+                structures: &[],
+            },
+            Biome::Mountains => BiomeProfile {
+                climate: [-0.5, -0.2],
+                height: HeightProfile {
+                    base: 64.0,
+                    amplitude: 48.0,
+                    frequency: 0.02,
+                },
+                surface: Voxel::STONE,
+                subsurface: Voxel::STONE,
+                decoration: Decoration::None,
+                structures: &[StructureRule {
+                    structure: Structure::Boulder,
+                    chance: 0.003,
+                }],
+            },
+            Biome::Ocean => BiomeProfile {
+                climate: [-0.2, 0.7],
+                height: HeightProfile {
+                    base: 12.0,
+                    amplitude: 6.0,
+                    frequency: 0.02,
+                },
+                surface: Voxel::SAND,
+                subsurface: Voxel::GRAVEL,
+                decoration: Decoration::None,
+                structures: &[],
+            },
+            Biome::Forest => BiomeProfile {
+                climate: [0.1, 0.5],
+                height: HeightProfile {
+                    base: 36.0,
+                    amplitude: 12.0,
+                    frequency: 0.02,
+                },
+                surface: Voxel::GRASS,
+                subsurface: Voxel::DIRT,
+                decoration: Decoration::Column {
+                    voxel: Voxel::LEAVES,
+                    min_height: 1,
+                    max_height: 2,
+                    density: 0.05,
+                },
+                structures: &[StructureRule {
+                    structure: Structure::Tree,
+                    chance: 0.02,
+                }],
+            },
+        }
+    }
+}
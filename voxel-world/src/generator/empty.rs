@@ -0,0 +1,15 @@
+use glam::IVec3;
+use voxel_core::VoxelBuffer;
+
+use super::WorldGenerator;
+use crate::terrain::CHUNK_SIZE;
+
+/// A world with no voxels at all. Useful for tests that only care about
+/// players and bodies.
+pub struct EmptyGenerator;
+
+impl WorldGenerator for EmptyGenerator {
+    fn generate(&self, _pos: IVec3) -> VoxelBuffer {
+        VoxelBuffer::new(CHUNK_SIZE.to_array())
+    }
+}
@@ -0,0 +1,69 @@
+use glam::IVec3;
+use voxel_core::{Voxel, VoxelBuffer};
+
+use super::WorldGenerator;
+use crate::terrain::CHUNK_SIZE;
+
+/// A superflat world built from horizontal layers.
+///
+/// Layers are listed bottom to top as `(voxel, thickness)` and stacked
+/// starting at `floor`. Everything below the floor and above the last layer
+/// is empty.
+pub struct FlatGenerator {
+    floor: i32,
+    layers: Vec<(Voxel, u32)>,
+}
+
+impl FlatGenerator {
+    pub fn new(floor: i32, layers: Vec<(Voxel, u32)>) -> Self {
+        Self { floor, layers }
+    }
+
+    fn voxel_at(&self, world_y: i32) -> Voxel {
+        let mut top = self.floor;
+        for &(voxel, thickness) in &self.layers {
+            top += thickness as i32;
+            if world_y < top {
+                return voxel;
+            }
+        }
+        Voxel::EMPTY
+    }
+}
+
+impl Default for FlatGenerator {
+    fn default() -> Self {
+        Self::new(0, vec![(Voxel::STONE, 28), (Voxel::DIRT, 4)])
+    }
+}
+
+impl WorldGenerator for FlatGenerator {
+    fn generate(&self, pos: IVec3) -> VoxelBuffer {
+        let mut buffer = VoxelBuffer::new(CHUNK_SIZE.to_array());
+
+        for y in 0..CHUNK_SIZE.y {
+            let world_y = pos.y * CHUNK_SIZE.y as i32 + y as i32;
+            if world_y < self.floor {
+                continue;
+            }
+
+            let voxel = self.voxel_at(world_y);
+            if voxel.is_empty() {
+                continue;
+            }
+
+            for x in 0..CHUNK_SIZE.x {
+                for z in 0..CHUNK_SIZE.z {
+                    buffer.set([x, y, z], voxel);
+                }
+            }
+        }
+
+        buffer
+    }
+
+    fn surface_height(&self, _world_x: i32, _world_z: i32) -> Option<i32> {
+        let thickness: u32 = self.layers.iter().map(|&(_, thickness)| thickness).sum();
+        (thickness > 0).then(|| self.floor + thickness as i32)
+    }
+}
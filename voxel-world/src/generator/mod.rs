@@ -0,0 +1,48 @@
+pub mod biome;
+pub mod empty;
+pub mod flat;
+pub mod ore;
+pub mod perlin;
+pub mod rng;
+pub mod structure;
+pub mod void;
+
+use glam::IVec3;
+use voxel_core::VoxelBuffer;
+
+pub use biome::Biome;
+pub use empty::EmptyGenerator;
+pub use flat::FlatGenerator;
+pub use ore::{OreRule, default_ores, place_ores};
+pub use perlin::{CaveConfig, PerlinConfig, PerlinGenerator};
+pub use structure::{Structure, StructureRule, VoxelEdit};
+pub use void::VoidGenerator;
+
+/// Produces the voxels for a single chunk.
+///
+/// Generation runs on a background thread, so implementations must be
+/// deterministic for a given chunk position and must not rely on the order
+/// chunks are requested in.
+pub trait WorldGenerator: Send + Sync {
+    fn generate(&self, pos: IVec3) -> VoxelBuffer;
+
+    /// Features rooted in a chunk that may reach into its neighbors, as
+    /// world-space edits. `chunk` is the chunk's base terrain before any
+    /// decoration, and placement must depend on nothing else.
+    fn decorate(&self, _pos: IVec3, _chunk: &VoxelBuffer) -> Vec<VoxelEdit> {
+        Vec::new()
+    }
+
+    /// The biome of the column at a world position, for generators that
+    /// have them.
+    fn biome_at(&self, _world_x: i32, _world_z: i32) -> Option<Biome> {
+        None
+    }
+
+    /// The first empty height above dry ground in a column, where a player
+    /// could stand. `None` when the column has nothing suitable, such as
+    /// open water or void.
+    fn surface_height(&self, _world_x: i32, _world_z: i32) -> Option<i32> {
+        None
+    }
+}
@@ -0,0 +1,138 @@
+use glam::IVec3;
+use voxel_core::{Voxel, VoxelBuffer};
+
+use super::rng::Rng;
+use crate::terrain::CHUNK_SIZE;
+
+const DIRECTIONS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
+/// How one kind of ore is spread through the world.
+#[derive(Clone, Debug)]
+pub struct OreRule {
+    pub ore: Voxel,
+    /// Only this block is replaced by the ore.
+    pub host: Voxel,
+    pub min_y: i32,
+    pub max_y: i32,
+    /// Number of steps in each vein's random walk.
+    pub vein_size: u32,
+    pub veins_per_chunk: u32,
+}
+
+pub fn default_ores() -> Vec<OreRule> {
+    vec![
+        OreRule {
+            ore: Voxel::COAL_ORE,
+            host: Voxel::STONE,
+            min_y: -64,
+            max_y: 96,
+            vein_size: 12,
+            veins_per_chunk: 8,
+        },
+        OreRule {
+            ore: Voxel::IRON_ORE,
+            host: Voxel::STONE,
+            min_y: -96,
+            max_y: 48,
+            vein_size: 8,
+            veins_per_chunk: 6,
+        },
+        OreRule {
+            ore: Voxel::GOLD_ORE,
+            host: Voxel::STONE,
+            min_y: -160,
+            max_y: 0,
+            vein_size: 6,
+            veins_per_chunk: 2,
+        },
+        OreRule {
+            ore: Voxel::DIAMOND_ORE,
+            host: Voxel::STONE,
+            min_y: -160,
+            max_y: -64,
+            vein_size: 4,
+            veins_per_chunk: 1,
+        },
+    ]
+}
+
+/// Places ore veins into an already generated chunk.
+///
+/// Veins are seeded per source chunk and may wander into neighbors, so every
+/// chunk replays the veins of each chunk close enough to reach it and keeps
+/// only the voxels that land inside itself. The result depends only on the seed and `pos`.
+pub fn place_ores(seed: u32, rules: &[OreRule], pos: IVec3, buffer: &mut VoxelBuffer) {
+    let size = CHUNK_SIZE.as_ivec3();
+    let origin = pos * size;
+
+    for (index, rule) in rules.iter().enumerate() {
+        let reach = rule.vein_size as i32;
+        if origin.y + size.y <= rule.min_y - reach || origin.y > rule.max_y + reach {
+            continue;
+        }
+
+        let rule_seed = seed.wrapping_add(0x9E37_79B9u32.wrapping_mul(index as u32 + 1));
+        // A vein moves one voxel per step, so long ones can come from
+        // further than the neighboring chunks.
+        let radius = IVec3::new(
+            rule.vein_size.div_ceil(CHUNK_SIZE.x) as i32,
+            rule.vein_size.div_ceil(CHUNK_SIZE.y) as i32,
+            rule.vein_size.div_ceil(CHUNK_SIZE.z) as i32,
+        );
+
+        for dx in -radius.x..=radius.x {
+            for dy in -radius.y..=radius.y {
+                for dz in -radius.z..=radius.z {
+                    let source = pos + IVec3::new(dx, dy, dz);
+                    let mut rng = Rng::at(rule_seed, source);
+
+                    for _ in 0..rule.veins_per_chunk {
+                        let start = source * size
+                            + IVec3::new(
+                                rng.range(0..=size.x - 1),
+                                rng.range(0..=size.y - 1),
+                                rng.range(0..=size.z - 1),
+                            );
+                        place_vein(rule, start, origin, &mut rng, buffer);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn place_vein(
+    rule: &OreRule,
+    start: IVec3,
+    origin: IVec3,
+    rng: &mut Rng,
+    buffer: &mut VoxelBuffer,
+) {
+    let in_range = (rule.min_y..=rule.max_y).contains(&start.y);
+    let mut world = start;
+
+    // The walk is always taken in full so the rng stays in step for the
+    // veins that follow, whether or not this one is placed.
+    for _ in 0..rule.vein_size {
+        let local = world - origin;
+        if in_range
+            && (rule.min_y..=rule.max_y).contains(&world.y)
+            && local.cmpge(IVec3::ZERO).all()
+            && local.cmplt(CHUNK_SIZE.as_ivec3()).all()
+        {
+            let local = local.as_uvec3().to_array();
+            if buffer.get(local) == rule.host {
+                buffer.set(local, rule.ore);
+            }
+        }
+
+        world += DIRECTIONS[rng.range(0..=5) as usize];
+    }
+}
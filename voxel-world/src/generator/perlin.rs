@@ -0,0 +1,378 @@
+use glam::IVec3;
+use noise::{
+    core::perlin::{perlin_2d, perlin_3d},
+    permutationtable::PermutationTable,
+};
+use voxel_core::{Voxel, VoxelBuffer};
+
+use super::{
+    WorldGenerator,
+    biome::{BIOMES, Biome, Decoration, HeightProfile},
+    ore::{OreRule, default_ores, place_ores},
+    rng::Rng,
+    structure::VoxelEdit,
+};
+use crate::terrain::CHUNK_SIZE;
+
+/// Frequency of the temperature and humidity noise.
+const CLIMATE_SCALE: f64 = 0.004;
+/// Width of the blend between biomes, in climate units.
+const BIOME_BLEND: f64 = 0.12;
+/// Surfaces above this height are covered in snow.
+const SNOW_LINE: i32 = 90;
+const SUBSURFACE_DEPTH: i32 = 4;
+const STRUCTURE_SALT: u32 = 0x5EED_0007;
+
+#[derive(Clone, Debug)]
+pub struct PerlinConfig {
+    /// How far 3D noise may push the surface up or down, in blocks. This is
+    /// what creates overhangs and arches; zero gives a plain heightfield.
+    pub overhang_strength: f64,
+    pub overhang_frequency: f64,
+    /// Open air below this height is filled with water.
+    pub sea_level: i32,
+    /// Caves below this height are filled with lava.
+    pub lava_level: i32,
+    pub caves: CaveConfig,
+    pub ores: Vec<OreRule>,
+}
+
+impl Default for PerlinConfig {
+    fn default() -> Self {
+        Self {
+            overhang_strength: 6.0,
+            overhang_frequency: 0.05,
+            sea_level: 24,
+            lava_level: -120,
+            caves: CaveConfig::default(),
+            ores: default_ores(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CaveConfig {
+    pub enabled: bool,
+    /// Tunnels follow the intersection of two noise fields' zero surfaces.
+    pub tunnel_frequency: f64,
+    /// Half-width of a tunnel in noise units. Larger values carve wider
+    /// tunnels.
+    pub tunnel_width: f64,
+    pub cavern_frequency: f64,
+    /// Noise value above which open caverns are carved.
+    pub cavern_threshold: f64,
+    /// Caverns are only carved at least this many blocks below the surface.
+    pub cavern_depth: i32,
+}
+
+impl Default for CaveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tunnel_frequency: 0.03,
+            tunnel_width: 0.06,
+            cavern_frequency: 0.015,
+            cavern_threshold: 0.6,
+            cavern_depth: 12,
+        }
+    }
+}
+
+/// Perlin terrain with biomes chosen from temperature and humidity noise.
+/// Heights are blended across biome borders, then shaped by 3D density
+/// noise for overhangs, carved into caves and seeded with ore veins.
+pub struct PerlinGenerator {
+    seed: u32,
+    config: PerlinConfig,
+    seed_table: PermutationTable,
+    temperature_table: PermutationTable,
+    humidity_table: PermutationTable,
+    overhang_table: PermutationTable,
+    tunnel_tables: [PermutationTable; 2],
+    cavern_table: PermutationTable,
+}
+
+struct Column {
+    height: i32,
+    biome: Biome,
+}
+
+impl PerlinGenerator {
+    pub fn new(seed: u32) -> Self {
+        Self::with_config(seed, PerlinConfig::default())
+    }
+
+    pub fn with_config(seed: u32, config: PerlinConfig) -> Self {
+        Self {
+            seed,
+            config,
+            seed_table: PermutationTable::new(seed),
+            temperature_table: PermutationTable::new(seed.wrapping_add(1)),
+            humidity_table: PermutationTable::new(seed.wrapping_add(2)),
+            overhang_table: PermutationTable::new(seed.wrapping_add(3)),
+            tunnel_tables: [
+                PermutationTable::new(seed.wrapping_add(4)),
+                PermutationTable::new(seed.wrapping_add(5)),
+            ],
+            cavern_table: PermutationTable::new(seed.wrapping_add(6)),
+        }
+    }
+
+    fn climate(&self, world_x: i32, world_z: i32) -> [f64; 2] {
+        let point = [
+            world_x as f64 * CLIMATE_SCALE,
+            world_z as f64 * CLIMATE_SCALE,
+        ];
+        [
+            perlin_2d(point.into(), &self.temperature_table),
+            perlin_2d(point.into(), &self.humidity_table),
+        ]
+    }
+
+    fn height(&self, profile: HeightProfile, world_x: i32, world_z: i32) -> f64 {
+        let noise_value = perlin_2d(
+            [
+                world_x as f64 * profile.frequency,
+                world_z as f64 * profile.frequency,
+            ]
+            .into(),
+            &self.seed_table,
+        );
+        profile.base + noise_value * profile.amplitude
+    }
+
+    fn column(&self, world_x: i32, world_z: i32) -> Column {
+        let [temperature, humidity] = self.climate(world_x, world_z);
+
+        let mut height = 0.0;
+        let mut total = 0.0;
+        let mut dominant = (Biome::Plains, 0.0);
+
+        for biome in BIOMES {
+            let profile = biome.profile();
+            let dt = temperature - profile.climate[0];
+            let dh = humidity - profile.climate[1];
+            let weight = (-(dt * dt + dh * dh) / (2.0 * BIOME_BLEND * BIOME_BLEND)).exp();
+
+            height += weight * self.height(profile.height, world_x, world_z);
+            total += weight;
+
+            if weight > dominant.1 {
+                dominant = (biome, weight);
+            }
+        }
+
+        let height = if total > 0.0 {
+            height / total
+        } else {
+            self.height(Biome::Plains.profile().height, world_x, world_z)
+        };
+
+        Column {
+            height: height as i32,
+            biome: dominant.0,
+        }
+    }
+
+    /// Distance below the surface, adjusted by 3D noise. Positive is solid.
+    fn depth(&self, height: i32, world: IVec3) -> f64 {
+        let depth = (height - world.y) as f64;
+        let strength = self.config.overhang_strength;
+
+        if strength <= 0.0 || depth.abs() > strength + SUBSURFACE_DEPTH as f64 {
+            return depth;
+        }
+
+        let point = world.as_dvec3() * self.config.overhang_frequency;
+        depth + perlin_3d(point.to_array().into(), &self.overhang_table) * strength
+    }
+
+    /// First empty height above the topmost solid voxel of a column,
+    /// ignoring caves.
+    fn surface_top(&self, height: i32, world_x: i32, world_z: i32) -> i32 {
+        let reach = self.config.overhang_strength.ceil() as i32;
+
+        for y in (height - reach - 1..=height + reach).rev() {
+            if self.depth(height, IVec3::new(world_x, y, world_z)) > 0.0 {
+                return y + 1;
+            }
+        }
+        height
+    }
+
+    /// The decoration standing in a column, as its voxel, bottom and height.
+    fn column_decoration(
+        &self,
+        biome: Biome,
+        height: i32,
+        world_x: i32,
+        world_z: i32,
+    ) -> Option<(Voxel, i32, i32)> {
+        let Decoration::Column {
+            voxel,
+            min_height,
+            max_height,
+            density,
+        } = biome.profile().decoration
+        else {
+            return None;
+        };
+
+        let mut rng = Rng::at(self.seed, IVec3::new(world_x, 0, world_z));
+        if rng.next_f64() >= density {
+            return None;
+        }
+
+        let top = self.surface_top(height, world_x, world_z);
+        (top >= self.config.sea_level).then(|| (voxel, top, rng.range(min_height..=max_height)))
+    }
+
+    /// The voxel at `world` in a column, before ores are placed.
+    fn terrain_voxel(
+        &self,
+        column: &Column,
+        decoration: Option<(Voxel, i32, i32)>,
+        world: IVec3,
+    ) -> Voxel {
+        let height = column.height;
+        let depth = self.depth(height, world);
+
+        if depth <= 0.0 {
+            match decoration {
+                Some((voxel, top, h)) if (top..top + h).contains(&world.y) => voxel,
+                _ if world.y < self.config.sea_level => Voxel::WATER,
+                _ => Voxel::EMPTY,
+            }
+        } else if self.is_cave(height, world) {
+            if world.y < self.config.lava_level {
+                Voxel::LAVA
+            } else {
+                Voxel::EMPTY
+            }
+        } else if depth > SUBSURFACE_DEPTH as f64 {
+            Voxel::STONE
+        } else if depth > 1.0 {
+            column.biome.profile().subsurface
+        } else if height > SNOW_LINE {
+            Voxel::SNOW
+        } else {
+            column.biome.profile().surface
+        }
+    }
+
+    fn is_cave(&self, height: i32, world: IVec3) -> bool {
+        let caves = &self.config.caves;
+        if !caves.enabled {
+            return false;
+        }
+
+        let point = world.as_dvec3() * caves.tunnel_frequency;
+        let [a, b] = self
+            .tunnel_tables
+            .each_ref()
+            .map(|table| perlin_3d(point.to_array().into(), table));
+        if a.abs() < caves.tunnel_width && b.abs() < caves.tunnel_width {
+            return true;
+        }
+
+        if height - world.y < caves.cavern_depth {
+            return false;
+        }
+
+        let point = world.as_dvec3() * caves.cavern_frequency;
+        perlin_3d(point.to_array().into(), &self.cavern_table) > caves.cavern_threshold
+    }
+}
+
+impl WorldGenerator for PerlinGenerator {
+    fn generate(&self, pos: IVec3) -> VoxelBuffer {
+        let mut buffer = VoxelBuffer::new(CHUNK_SIZE.to_array());
+
+        for x in 0..CHUNK_SIZE.x {
+            for z in 0..CHUNK_SIZE.z {
+                let world_x = pos.x * CHUNK_SIZE.x as i32 + x as i32;
+                let world_z = pos.z * CHUNK_SIZE.z as i32 + z as i32;
+
+                let column = self.column(world_x, world_z);
+                let decoration =
+                    self.column_decoration(column.biome, column.height, world_x, world_z);
+
+                for y in 0..CHUNK_SIZE.y {
+                    let world =
+                        IVec3::new(world_x, pos.y * CHUNK_SIZE.y as i32 + y as i32, world_z);
+                    let voxel = self.terrain_voxel(&column, decoration, world);
+                    buffer.set([x, y, z], voxel);
+                }
+            }
+        }
+
+        place_ores(self.seed, &self.config.ores, pos, &mut buffer);
+
+        buffer
+    }
+
+    fn decorate(&self, pos: IVec3, chunk: &VoxelBuffer) -> Vec<VoxelEdit> {
+        let mut edits = Vec::new();
+        let origin = pos * CHUNK_SIZE.as_ivec3();
+
+        for x in 0..CHUNK_SIZE.x {
+            for z in 0..CHUNK_SIZE.z {
+                // Above the top layer the terrain is worked out from the
+                // noise, so the decision only depends on the seed and never
+                // on whether the chunk above exists yet.
+                let above_top = || {
+                    let world = origin + IVec3::new(x as i32, CHUNK_SIZE.y as i32, z as i32);
+                    let column = self.column(world.x, world.z);
+                    let decoration =
+                        self.column_decoration(column.biome, column.height, world.x, world.z);
+                    self.terrain_voxel(&column, decoration, world)
+                };
+                let Some(y) = (0..CHUNK_SIZE.y).rev().find(|&y| {
+                    let above = match y + 1 {
+                        above if above < CHUNK_SIZE.y => chunk.get([x, above, z]),
+                        _ => above_top(),
+                    };
+                    !chunk.get([x, y, z]).is_empty() && above.is_empty()
+                }) else {
+                    continue;
+                };
+
+                let ground = chunk.get([x, y, z]);
+                let base = origin + IVec3::new(x as i32, y as i32 + 1, z as i32);
+                let biome = self.column(base.x, base.z).biome;
+
+                let mut rng = Rng::at(
+                    self.seed.wrapping_add(STRUCTURE_SALT),
+                    IVec3::new(base.x, pos.y, base.z),
+                );
+
+                for rule in biome.profile().structures {
+                    if rng.next_f64() < rule.chance && rule.structure.can_place_on(ground) {
+                        rule.structure.place(base, &mut rng, &mut edits);
+                        break;
+                    }
+                }
+            }
+        }
+
+        edits
+    }
+
+    fn biome_at(&self, world_x: i32, world_z: i32) -> Option<Biome> {
+        Some(self.column(world_x, world_z).biome)
+    }
+
+    fn surface_height(&self, world_x: i32, world_z: i32) -> Option<i32> {
+        let Column { height, biome } = self.column(world_x, world_z);
+        if self
+            .column_decoration(biome, height, world_x, world_z)
+            .is_some()
+        {
+            return None;
+        }
+
+        let top = self.surface_top(height, world_x, world_z);
+        let ground = IVec3::new(world_x, top - 1, world_z);
+        (top >= self.config.sea_level && !self.is_cave(height, ground)).then_some(top)
+    }
+}
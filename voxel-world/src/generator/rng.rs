@@ -0,0 +1,48 @@
+use std::ops::RangeInclusive;
+
+use glam::IVec3;
+
+/// Small splitmix64 generator for deterministic placement decisions.
+///
+/// Seeding from a world seed and a position gives every column or chunk its
+/// own stream, so results never depend on generation order.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn at(seed: u32, pos: IVec3) -> Self {
+        let mut h = seed as u64;
+        for v in pos.to_array() {
+            h = mix(h ^ (v as u32 as u64));
+        }
+        Self(h)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        mix(self.0)
+    }
+
+    /// Uniform value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn range(&mut self, range: RangeInclusive<i32>) -> i32 {
+        let (lo, hi) = (*range.start(), *range.end());
+        if hi <= lo {
+            return lo;
+        }
+        let span = (hi as i64 - lo as i64 + 1) as u64;
+        lo + (self.next_u64() % span) as i32
+    }
+}
+
+fn mix(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
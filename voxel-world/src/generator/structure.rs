@@ -0,0 +1,108 @@
+use glam::IVec3;
+use voxel_core::Voxel;
+
+use super::rng::Rng;
+
+/// A single voxel write in world space, produced by decoration.
+#[derive(Copy, Clone, Debug)]
+pub struct VoxelEdit {
+    pub pos: IVec3,
+    pub voxel: Voxel,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Structure {
+    Tree,
+    Boulder,
+    Hut,
+}
+
+/// Chance per surface column that a structure is rooted there.
+#[derive(Copy, Clone, Debug)]
+pub struct StructureRule {
+    pub structure: Structure,
+    pub chance: f64,
+}
+
+impl Structure {
+    pub fn can_place_on(self, ground: Voxel) -> bool {
+        match self {
+            Structure::Tree | Structure::Hut => ground == Voxel::GRASS,
+            Structure::Boulder => matches!(
+                ground,
+                Voxel::GRASS | Voxel::DIRT | Voxel::STONE | Voxel::SNOW
+            ),
+        }
+    }
+
+    /// Emits the structure's voxels with `base` as the first empty voxel
+    /// above the ground it stands on.
+    pub fn place(self, base: IVec3, rng: &mut Rng, edits: &mut Vec<VoxelEdit>) {
+        let mut push = |offset: IVec3, voxel: Voxel| {
+            edits.push(VoxelEdit {
+                pos: base + offset,
+                voxel,
+            })
+        };
+
+        match self {
+            Structure::Tree => {
+                let height = rng.range(4..=6);
+
+                for dy in height - 2..=height + 1 {
+                    let radius: i32 = if dy > height { 1 } else { 2 };
+                    for dx in -radius..=radius {
+                        for dz in -radius..=radius {
+                            if radius == 2 && dx.abs() == 2 && dz.abs() == 2 {
+                                continue;
+                            }
+                            push(IVec3::new(dx, dy, dz), Voxel::LEAVES);
+                        }
+                    }
+                }
+
+                for dy in 0..height {
+                    push(IVec3::new(0, dy, 0), Voxel::LOG);
+                }
+            }
+            Structure::Boulder => {
+                let radius = rng.range(1..=2);
+                let center = IVec3::new(0, radius - 1, 0);
+
+                for dx in -radius..=radius {
+                    for dy in -radius..=radius {
+                        for dz in -radius..=radius {
+                            let offset = IVec3::new(dx, dy, dz);
+                            if offset.length_squared() <= radius * radius + 1 {
+                                push(center + offset, Voxel::STONE);
+                            }
+                        }
+                    }
+                }
+            }
+            Structure::Hut => {
+                let size = 5;
+                let wall_height = 3;
+
+                for dx in 0..size {
+                    for dz in 0..size {
+                        push(IVec3::new(dx, wall_height, dz), Voxel::PLANKS);
+
+                        let edge = dx == 0 || dz == 0 || dx == size - 1 || dz == size - 1;
+                        let door = dx == size / 2 && dz == 0;
+                        if !edge {
+                            continue;
+                        }
+
+                        for dy in 0..wall_height {
+                            if door && dy < 2 {
+                                continue;
+                            }
+                            push(IVec3::new(dx, dy, dz), Voxel::PLANKS);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use glam::IVec3;
+use voxel_core::{Voxel, VoxelBuffer};
+
+use super::WorldGenerator;
+use crate::terrain::CHUNK_SIZE;
+
+/// An empty world with a single square platform centered on the origin to
+/// spawn on.
+///
+/// The platform is a single layer of `platform` whose top is at
+/// `platform_height`, reaching `platform_radius` voxels out from the origin.
+pub struct VoidGenerator {
+    platform_radius: i32,
+    platform_height: i32,
+    platform: Voxel,
+}
+
+impl VoidGenerator {
+    pub fn new(platform_radius: i32, platform_height: i32, platform: Voxel) -> Self {
+        Self {
+            platform_radius,
+            platform_height,
+            platform,
+        }
+    }
+}
+
+impl Default for VoidGenerator {
+    fn default() -> Self {
+        Self::new(4, 32, Voxel::STONE)
+    }
+}
+
+impl WorldGenerator for VoidGenerator {
+    fn generate(&self, pos: IVec3) -> VoxelBuffer {
+        let mut buffer = VoxelBuffer::new(CHUNK_SIZE.to_array());
+        let origin = pos * CHUNK_SIZE.as_ivec3();
+
+        for x in 0..CHUNK_SIZE.x {
+            for z in 0..CHUNK_SIZE.z {
+                let world_x = origin.x + x as i32;
+                let world_z = origin.z + z as i32;
+
+                if world_x.abs() > self.platform_radius || world_z.abs() > self.platform_radius {
+                    continue;
+                }
+
+                let y = self.platform_height - 1 - origin.y;
+                if (0..CHUNK_SIZE.y as i32).contains(&y) {
+                    buffer.set([x, y as u32, z], self.platform);
+                }
+            }
+        }
+
+        buffer
+    }
+
+    fn surface_height(&self, world_x: i32, world_z: i32) -> Option<i32> {
+        let on_platform =
+            world_x.abs() <= self.platform_radius && world_z.abs() <= self.platform_radius;
+        on_platform.then_some(self.platform_height)
+    }
+}
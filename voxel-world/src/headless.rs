@@ -0,0 +1,107 @@
+//! Drives a world by hand instead of on a timer.
+//!
+//! [`Headless`] wraps a world whose terrain is generated inline and steps
+//! it only when asked, so the same input always leads to the same state.
+//! It collects every event the world sends for later inspection.
+
+use crate::{
+    VoxelWorld,
+    command::WorldCommand,
+    config::WorldConfig,
+    envelope::Envelope,
+    event::WorldEvent,
+    generator::WorldGenerator,
+    request::{Accepted, Call, Connect, PendingRequest},
+    terrain::CHUNK_RENDER_DISTANCE,
+};
+
+pub struct Headless {
+    world: VoxelWorld,
+    commands: Vec<Envelope<WorldCommand>>,
+    requests: Vec<PendingRequest>,
+    events: Vec<Envelope<WorldEvent>>,
+}
+
+impl Headless {
+    pub fn new(generator: impl WorldGenerator + 'static, config: WorldConfig) -> Self {
+        Self {
+            world: VoxelWorld::headless(generator, config),
+            commands: Vec::new(),
+            requests: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn world(&self) -> &VoxelWorld {
+        &self.world
+    }
+
+    pub fn world_mut(&mut self) -> &mut VoxelWorld {
+        &mut self.world
+    }
+
+    /// Queues a command from a player for the next step.
+    pub fn send(&mut self, from: u32, command: impl Into<WorldCommand>) {
+        self.commands.push(Envelope::from(from, command));
+    }
+
+    /// Queues a command from the server console for the next step.
+    pub fn console(&mut self, command: impl Into<WorldCommand>) {
+        self.commands.push(Envelope::broadcast(command));
+    }
+
+    /// Queues a request for the next step. Its reply is ready once that
+    /// step has run.
+    pub fn request(&mut self, request: PendingRequest) {
+        self.requests.push(request);
+    }
+
+    /// Connects a player and steps once so the world can accept them.
+    pub fn connect(&mut self, name: &str) -> Accepted {
+        self.connect_with_view(name, CHUNK_RENDER_DISTANCE)
+    }
+
+    /// Like [`Headless::connect`], asking for `view_distance` chunks.
+    pub fn connect_with_view(&mut self, name: &str, view_distance: i32) -> Accepted {
+        let (call, mut reply) = Call::new(Connect {
+            name: name.to_string(),
+            view_distance,
+        });
+        self.request(PendingRequest::Connect(call));
+        self.step();
+        reply
+            .try_recv()
+            .expect("the world answers a connect within the tick it arrives")
+    }
+
+    /// Runs one tick with everything queued since the last one.
+    pub fn step(&mut self) {
+        let commands = std::mem::take(&mut self.commands);
+        let requests = std::mem::take(&mut self.requests);
+        let events = self.world.step(commands, requests);
+        self.events.extend(events);
+    }
+
+    pub fn step_n(&mut self, ticks: u32) {
+        for _ in 0..ticks {
+            self.step();
+        }
+    }
+
+    /// Every event collected since the last `take_events`, oldest first.
+    pub fn events(&self) -> &[Envelope<WorldEvent>] {
+        &self.events
+    }
+
+    pub fn take_events(&mut self) -> Vec<Envelope<WorldEvent>> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Collected events a player would have received, broadcasts included.
+    pub fn events_for(&self, id: u32) -> impl Iterator<Item = &WorldEvent> {
+        self.events
+            .iter()
+            .filter(move |e| e.to.is_none_or(|to| to == id))
+            .map(|e| &e.payload)
+    }
+}
@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use voxel_core::Voxel;
+
+/// Slots in a player's inventory. The first `HOTBAR_SIZE` are the hotbar.
+pub const INVENTORY_SIZE: usize = 36;
+pub const HOTBAR_SIZE: usize = 9;
+pub const MAX_STACK: u8 = 64;
+/// What creative players place from each empty hotbar slot.
+pub const CREATIVE_PALETTE: [Voxel; HOTBAR_SIZE] = [
+    Voxel::GRASS,
+    Voxel::DIRT,
+    Voxel::STONE,
+    Voxel::SAND,
+    Voxel::GRAVEL,
+    Voxel::LOG,
+    Voxel::PLANKS,
+    Voxel::LEAVES,
+    Voxel::SNOW,
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ItemStack {
+    pub voxel: Voxel,
+    pub count: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Inventory {
+    slots: Vec<Option<ItemStack>>,
+    selected: usize,
+    /// Set by every change so the owner can be sent the new contents.
+    #[serde(skip)]
+    changed: bool,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            slots: vec![None; INVENTORY_SIZE],
+            selected: 0,
+            changed: false,
+        }
+    }
+}
+
+impl Inventory {
+    pub fn slots(&self) -> &[Option<ItemStack>] {
+        &self.slots
+    }
+
+    pub fn hotbar(&self) -> &[Option<ItemStack>] {
+        &self.slots[..HOTBAR_SIZE]
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_item(&self) -> Option<ItemStack> {
+        self.slots[self.selected]
+    }
+
+    pub fn select(&mut self, slot: usize) {
+        if slot < HOTBAR_SIZE && slot != self.selected {
+            self.selected = slot;
+            self.changed = true;
+        }
+    }
+
+    /// Adds items to existing stacks first, then to empty slots, and
+    /// returns how many did not fit.
+    pub fn add(&mut self, voxel: Voxel, mut count: u32) -> u32 {
+        for stack in self.slots.iter_mut().flatten() {
+            if count == 0 {
+                break;
+            }
+            if stack.voxel == voxel && stack.count < MAX_STACK {
+                let moved = count.min((MAX_STACK - stack.count) as u32);
+                stack.count += moved as u8;
+                count -= moved;
+                self.changed = true;
+            }
+        }
+
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if count == 0 {
+                break;
+            }
+            let moved = count.min(MAX_STACK as u32);
+            *slot = Some(ItemStack {
+                voxel,
+                count: moved as u8,
+            });
+            count -= moved;
+            self.changed = true;
+        }
+
+        count
+    }
+
+    /// Takes one item out of the selected slot.
+    pub fn take_selected(&mut self) -> Option<Voxel> {
+        let slot = &mut self.slots[self.selected];
+        let stack = slot.as_mut()?;
+
+        stack.count -= 1;
+        let voxel = stack.voxel;
+        if stack.count == 0 {
+            *slot = None;
+        }
+        self.changed = true;
+        Some(voxel)
+    }
+
+    /// Moves up to `count` items from one slot onto an empty slot or a
+    /// stack of the same item.
+    pub fn move_items(&mut self, from: usize, to: usize, count: u8) {
+        if from == to || from >= self.slots.len() || to >= self.slots.len() {
+            return;
+        }
+        let Some(mut source) = self.slots[from] else {
+            return;
+        };
+
+        let space = match self.slots[to] {
+            None => MAX_STACK,
+            Some(target) if target.voxel == source.voxel => MAX_STACK - target.count,
+            Some(_) => return,
+        };
+        let moved = count.min(source.count).min(space);
+        if moved == 0 {
+            return;
+        }
+
+        let target = self.slots[to].get_or_insert(ItemStack {
+            voxel: source.voxel,
+            count: 0,
+        });
+        target.count += moved;
+        source.count -= moved;
+        self.slots[from] = (source.count > 0).then_some(source);
+        self.changed = true;
+    }
+
+    pub fn swap(&mut self, a: usize, b: usize) {
+        if a != b && a < self.slots.len() && b < self.slots.len() {
+            self.slots.swap(a, b);
+            self.changed = true;
+        }
+    }
+
+    /// Whether anything changed since the last call.
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+}
@@ -1,57 +1,152 @@
 pub mod bridge;
+mod chat;
 pub mod command;
+pub mod config;
+mod controller;
+mod decoration;
+mod dropped;
+pub mod entity;
 pub mod envelope;
 pub mod event;
+mod falling;
+mod fluid;
+pub mod generator;
+pub mod headless;
+pub mod inventory;
+pub mod movement;
 pub mod physics;
 pub mod player;
+pub mod replay;
 pub mod request;
-mod terrain;
+pub mod slash;
+mod spawn;
+pub mod stats;
+pub mod streaming;
+pub mod terrain;
+pub mod time;
 
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
     time::{Duration, Instant},
 };
 
 use glam::{IVec3, Vec3};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+use voxel_core::{Voxel, VoxelBuffer};
 
 use crate::{
     command::*,
-    envelope::Envelope,
+    config::WorldConfig,
+    dropped::{DroppedItems, Pickup},
+    entity::Entities,
+    envelope::{CONSOLE, Envelope},
     event::*,
+    falling::{FallingBlocks, Settled},
+    fluid::Fluids,
+    generator::{Biome, WorldGenerator},
+    inventory::{CREATIVE_PALETTE, ItemStack},
+    movement::MotionState,
     physics::Physics,
-    player::{PlayerInput, PlayerState},
-    request::{PendingRequest, Pong},
+    player::{ChunkInterest, Digging, GameMode, MovementMode, PlayerInput, PlayerState},
+    replay::Recorder,
+    request::{Accepted, Connect, PendingRequest, Pong},
+    slash::{CommandError, Registry, Sender, SlashCommand},
+    stats::{Phase, PhaseTimer, StatsReport, TickStats},
+    streaming::{ChunkStream, priority},
     terrain::{
-        CHUNK_RENDER_DISTANCE, Terrain, chunk_in_range, chunks_in_box, chunks_in_radius,
-        world_to_chunk_pos,
+        Terrain, WORLD_BOTTOM, Y_RANGE, chunk_in_range, chunks_in_box, chunks_in_radius,
+        voxel_to_chunk_pos, world_to_chunk_pos,
     },
+    time::WorldClock,
 };
 
 pub const PHYSICS_RADIUS: i32 = 2;
 
-pub const MOVEMENT_SPEED: f32 = 10.0;
+pub const MOVEMENT_SPEED: f32 = 5.0;
 pub const SPRINT_MULTIPLIER: f32 = 2.0;
 pub const THRUST: f32 = 20.0;
+pub const SPECTATOR_SPEED: f32 = 15.0;
+
+pub const ACCELERATION: f32 = 40.0;
+pub const AIR_ACCELERATION: f32 = 10.0;
+pub const FRICTION: f32 = 30.0;
+/// Downward acceleration of players, items and falling blocks alike.
+pub const GRAVITY: f32 = 25.0;
+/// Downward acceleration of flying players, which their thrust overcomes.
+pub const FLIGHT_GRAVITY: f32 = 9.81;
+pub const JUMP_SPEED: f32 = 8.5;
+pub const SWIM_ACCELERATION: f32 = 30.0;
+pub const TERMINAL_VELOCITY: f32 = 60.0;
+
+pub const PLAYER_RADIUS: f32 = 0.5;
+pub const PLAYER_HALF_HEIGHT: f32 = 1.0;
+/// Bodies that fall this far below the world are gone for good.
+pub const KILL_HEIGHT: f32 = (WORLD_BOTTOM - 64) as f32;
 
 pub const TICK_RATE: f32 = 60.0;
 pub const DT: f32 = 1.0 / TICK_RATE;
+/// How many ticks the world may fall behind before it stops catching up.
+const MAX_CATCH_UP_TICKS: u32 = 10;
+const OVERRUN_WARNING_INTERVAL: Duration = Duration::from_secs(5);
 
 pub struct VoxelWorld {
-    players: HashMap<u32, PlayerState>,
+    config: WorldConfig,
+    spawn: Vec3,
+    players: BTreeMap<u32, PlayerState>,
     terrain: Terrain,
     physics: Physics,
+    fluids: Fluids,
+    entities: Entities,
+    falling: FallingBlocks,
+    items: DroppedItems,
+    commands: Registry,
+    clock: WorldClock,
+    stats: TickStats,
+    recorder: Option<Recorder>,
+    /// Running hash of every chunk edit, part of the state hash.
+    terrain_hash: u64,
     events: Vec<Envelope<WorldEvent>>,
     next_id: u32,
     tick: u64,
 }
 
 impl VoxelWorld {
-    pub fn new(seed: u32) -> Self {
+    pub fn new(generator: impl WorldGenerator + 'static) -> Self {
+        Self::with_config(generator, WorldConfig::default())
+    }
+
+    pub fn with_config(generator: impl WorldGenerator + 'static, config: WorldConfig) -> Self {
+        let generator: Arc<dyn WorldGenerator> = Arc::new(generator);
+        let spawn = spawn::find_spawn(generator.clone(), &config.spawn);
+        Self::with_terrain(Terrain::new(generator), spawn, config)
+    }
+
+    /// A world that generates terrain on the calling thread instead of in
+    /// the background, so stepping it with the same input always gives the
+    /// same result. Meant for tests and tools driving it through `step`.
+    pub fn headless(generator: impl WorldGenerator + 'static, config: WorldConfig) -> Self {
+        let generator: Arc<dyn WorldGenerator> = Arc::new(generator);
+        let spawn = spawn::find_spawn(generator.clone(), &config.spawn);
+        Self::with_terrain(Terrain::synchronous(generator), spawn, config)
+    }
+
+    fn with_terrain(terrain: Terrain, spawn: Vec3, config: WorldConfig) -> Self {
         Self {
-            players: HashMap::new(),
-            terrain: Terrain::new(seed),
+            config,
+            spawn,
+            players: BTreeMap::new(),
+            terrain,
             physics: Physics::init(),
+            fluids: Fluids::default(),
+            entities: Entities::default(),
+            falling: FallingBlocks::default(),
+            items: DroppedItems::default(),
+            commands: Registry::default(),
+            clock: WorldClock::default(),
+            stats: TickStats::default(),
+            recorder: None,
+            terrain_hash: 0,
             events: Vec::new(),
             next_id: 1,
             tick: 0,
@@ -65,50 +160,195 @@ impl VoxelWorld {
         event_tx: UnboundedSender<Envelope<WorldEvent>>,
     ) {
         let mut next_tick = Instant::now();
+        let mut last_warning: Option<Instant> = None;
 
         loop {
-            for event in self.tick(&mut command_rx, &mut req_rx, DT) {
+            let started = Instant::now();
+            let commands: Vec<_> = std::iter::from_fn(|| command_rx.try_recv().ok()).collect();
+            let requests: Vec<_> = std::iter::from_fn(|| req_rx.try_recv().ok()).collect();
+            for event in self.step(commands, requests) {
                 let _ = event_tx.send(event);
             }
+            let elapsed = started.elapsed();
+            let now = Instant::now();
+
+            if elapsed.as_secs_f32() > DT {
+                self.stats.overrun();
 
+                if last_warning.is_none_or(|at| now - at >= OVERRUN_WARNING_INTERVAL) {
+                    last_warning = Some(now);
+                    let report = self.stats();
+                    let slowest = report.slowest().map_or("none", |p| p.phase.name());
+                    eprintln!(
+                        "tick {} took {:.1}ms of {:.1}ms, slowest phase: {slowest}",
+                        self.tick,
+                        elapsed.as_secs_f32() * 1000.0,
+                        DT * 1000.0,
+                    );
+                }
+            }
+
+            // Run late ticks back to back to catch up, but give up on the
+            // backlog once it grows too long rather than racing forever.
             next_tick += Duration::from_secs_f32(DT);
-            std::thread::sleep(next_tick.saturating_duration_since(Instant::now()));
+            let behind = now.saturating_duration_since(next_tick).as_secs_f32() / DT;
+            if behind > MAX_CATCH_UP_TICKS as f32 {
+                self.stats.skip(behind as u64);
+                next_tick = now;
+            }
+
+            std::thread::sleep(next_tick.saturating_duration_since(now));
         }
     }
 
+    /// Tick timing over the last few seconds.
+    pub fn stats(&self) -> StatsReport {
+        self.stats.report(self.tick)
+    }
+
+    /// Where new players appear.
+    pub fn spawn(&self) -> Vec3 {
+        self.spawn
+    }
+
+    /// World time in ticks since the first sunrise.
+    pub fn time(&self) -> u64 {
+        self.clock.time(self.tick)
+    }
+
+    pub fn biome_at(&self, pos: Vec3) -> Option<Biome> {
+        self.terrain.biome_at(pos)
+    }
+
+    /// Ticks run so far.
+    pub fn tick_count(&self) -> u64 {
+        self.tick
+    }
+
+    pub fn player(&self, id: u32) -> Option<&PlayerState> {
+        self.players.get(&id)
+    }
+
+    /// Ids of every connected player, lowest first.
+    pub fn player_ids(&self) -> Vec<u32> {
+        self.players.keys().copied().collect()
+    }
+
+    pub fn player_position(&self, id: u32) -> Option<Vec3> {
+        let player = self.players.get(&id)?;
+        Some(self.physics.position(player.body))
+    }
+
+    /// The chunk at `pos`, if it has been generated.
+    pub fn chunk(&self, pos: IVec3) -> Option<Arc<VoxelBuffer>> {
+        self.terrain.get(pos)
+    }
+
+    pub fn voxel(&self, pos: IVec3) -> Option<Voxel> {
+        self.terrain.voxel(pos)
+    }
+
+    /// Whether the chunk currently has colliders in the physics world.
+    pub fn has_physics_chunk(&self, pos: IVec3) -> bool {
+        self.physics.has_chunk(pos)
+    }
+
+    /// Runs one tick right away with the given input and returns the
+    /// events it produced. `run` calls this on a timer; tests can call it
+    /// directly to step the world as fast as they like.
+    pub fn step(
+        &mut self,
+        commands: impl IntoIterator<Item = Envelope<WorldCommand>>,
+        requests: impl IntoIterator<Item = PendingRequest>,
+    ) -> Vec<Envelope<WorldEvent>> {
+        let commands = commands.into_iter().collect();
+        let requests = requests.into_iter().collect();
+        self.tick(commands, requests, DT)
+    }
+
     fn tick(
         &mut self,
-        command_rx: &mut UnboundedReceiver<Envelope<WorldCommand>>,
-        req_rx: &mut UnboundedReceiver<PendingRequest>,
+        commands: Vec<Envelope<WorldCommand>>,
+        requests: Vec<PendingRequest>,
         dt: f32,
     ) -> Vec<Envelope<WorldEvent>> {
         self.tick += 1;
+        let mut timer = PhaseTimer::start();
 
-        while let Ok(cmd) = command_rx.try_recv() {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.begin(self.tick, &commands, &requests);
+        }
+
+        for cmd in commands {
             self.execute(cmd);
         }
+        timer.lap(Phase::Commands);
 
-        while let Ok(req) = req_rx.try_recv() {
+        for req in requests {
             self.handle(req);
         }
+        timer.lap(Phase::Requests);
+
+        self.dig();
 
         // movement
-        self.process_player_inputs();
+        self.release_held_players();
+        self.push_players(dt);
+        self.process_player_inputs(dt);
+        self.apply_fluid_forces();
+        timer.lap(Phase::Inputs);
+        self.update_falling_blocks();
+        self.sync_modified_chunks();
+        timer.lap(Phase::Simulation);
         self.physics.step(dt);
+        timer.lap(Phase::PhysicsStep);
+        self.entities.sync_bodies(&self.physics);
+        self.respawn_fallen_players();
+        self.pick_up_items();
+        self.sync_inventories();
         self.broadcast_movement();
+        timer.lap(Phase::Movement);
 
         // terrain
         self.sync_player_chunks();
+        self.sync_player_biomes();
+        timer.lap(Phase::ChunkSync);
+        self.fluids
+            .step(&mut self.terrain, self.tick, &self.config.fluids);
+        timer.lap(Phase::Fluids);
         self.poll_terrain();
+        timer.lap(Phase::TerrainPoll);
+        self.stream_chunks();
+        timer.lap(Phase::ChunkStreaming);
+
+        self.replicate_entities();
+        self.sync_time();
+        self.send_stats();
+        timer.lap(Phase::Replication);
 
         // physics
         self.sync_physics_chunks();
+        timer.lap(Phase::PhysicsChunks);
+
+        self.stats.record(timer);
+        self.finish_recording();
 
         std::mem::take(&mut self.events)
     }
 
     fn execute(&mut self, cmd: Envelope<WorldCommand>) {
-        let id = cmd.from.unwrap();
+        // Commands without a sender come from the server itself.
+        let Some(id) = cmd.from else {
+            match cmd.payload {
+                WorldCommand::SetGameMode(cmd) => self.set_game_mode(cmd.id, cmd.mode),
+                WorldCommand::Chat(cmd) => {
+                    let line = cmd.text.trim();
+                    self.run_command(Sender::Console, line.strip_prefix('/').unwrap_or(line));
+                }
+                _ => (),
+            }
+            return;
+        };
 
         match cmd.payload {
             WorldCommand::Disconnect => self.remove_player(id),
@@ -117,14 +357,417 @@ impl VoxelWorld {
                     player.input = cmd.input;
                 }
             }
+            WorldCommand::SetMovementMode(cmd) => self.set_movement_mode(id, cmd.mode),
+            WorldCommand::SetGameMode(_) => (),
+            WorldCommand::BreakBlock(cmd) => self.break_block(id, IVec3::from_array(cmd.pos)),
+            WorldCommand::PlaceBlock(cmd) => self.place_block(id, IVec3::from_array(cmd.pos)),
+            WorldCommand::SelectSlot(cmd) => {
+                if let Some(player) = self.players.get_mut(&id) {
+                    player.inventory.select(cmd.slot as usize);
+                }
+            }
+            WorldCommand::MoveItems(cmd) => {
+                if let Some(player) = self.players.get_mut(&id) {
+                    player
+                        .inventory
+                        .move_items(cmd.from as usize, cmd.to as usize, cmd.count);
+                }
+            }
+            WorldCommand::SwapSlots(cmd) => {
+                if let Some(player) = self.players.get_mut(&id) {
+                    player.inventory.swap(cmd.a as usize, cmd.b as usize);
+                }
+            }
+            WorldCommand::Chat(cmd) => self.chat(id, &cmd.text),
+            WorldCommand::WatchStats(cmd) => {
+                if let Some(player) = self.players.get_mut(&id) {
+                    player.watching_stats = cmd.enabled;
+                }
+            }
+            WorldCommand::AckMovement(cmd) => {
+                if let Some(player) = self.players.get_mut(&id) {
+                    for (subject, tick) in cmd.acks {
+                        if let Some(sync) = player.motion.get_mut(&subject) {
+                            sync.ack(tick);
+                        }
+                    }
+                }
+            }
+            WorldCommand::AckChunks(cmd) => {
+                if let Some(player) = self.players.get_mut(&id) {
+                    player
+                        .stream
+                        .ack(cmd.count, self.tick, &self.config.streaming);
+                }
+            }
+            WorldCommand::SetViewDistance(cmd) => self.set_view_distance(id, cmd.distance),
+        }
+    }
+
+    fn chat(&mut self, id: u32, text: &str) {
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
+        };
+        let Some(text) = chat::sanitize(text, &self.config.chat) else {
+            return;
+        };
+        if !player.chat.allow(self.tick, &self.config.chat) {
+            return;
+        }
+
+        if let Some(line) = text.strip_prefix('/') {
+            self.run_command(Sender::Player(id), line);
+            return;
+        }
+
+        self.events.push(Envelope::broadcast(ChatMessage {
+            from: id,
+            name: player.name.clone(),
+            text,
+            timestamp: self.tick,
+        }));
+    }
+
+    /// Adds a slash command, replacing any built-in one with the same name.
+    pub fn register_command(&mut self, command: SlashCommand) {
+        self.commands.register(command);
+    }
+
+    fn run_command(&mut self, sender: Sender, line: &str) {
+        let (text, error) = match slash::run(self, sender, line) {
+            Ok(text) => (text, false),
+            Err(text) => (text, true),
+        };
+        if text.is_empty() {
+            return;
+        }
+
+        let to = match sender {
+            Sender::Console => CONSOLE,
+            Sender::Player(id) => id,
+        };
+        self.events
+            .push(Envelope::to(to, CommandReply { text, error }));
+    }
+
+    fn is_operator(&self, sender: Sender) -> bool {
+        match sender {
+            Sender::Console => true,
+            Sender::Player(id) => self
+                .players
+                .get(&id)
+                .is_some_and(|player| self.config.commands.operators.contains(&player.name)),
+        }
+    }
+
+    /// Looks a player up by name, or by id if no name matches.
+    fn find_player(&self, name: &str) -> Result<u32, CommandError> {
+        self.players
+            .iter()
+            .find(|(_, player)| player.name == name)
+            .map(|(&id, _)| id)
+            .or_else(|| name.parse().ok().filter(|id| self.players.contains_key(id)))
+            .ok_or_else(|| CommandError::Invalid(format!("no player named '{name}'")))
+    }
+
+    fn set_time(&mut self, time: u64) {
+        self.clock.set(self.tick, time);
+        self.events.push(Envelope::broadcast(self.time_changed()));
+    }
+
+    fn set_time_frozen(&mut self, frozen: bool) {
+        if frozen {
+            self.clock.freeze(self.tick);
+        } else {
+            self.clock.resume(self.tick);
+        }
+        self.events.push(Envelope::broadcast(self.time_changed()));
+    }
+
+    fn time_changed(&self) -> TimeChanged {
+        TimeChanged {
+            tick: self.tick,
+            time: self.time(),
+            day_length: self.config.time.day_length,
+            frozen: self.clock.is_frozen(),
+        }
+    }
+
+    /// Sends the tick timing once a second to players who asked for it.
+    fn send_stats(&mut self) {
+        if !self.tick.is_multiple_of(TICK_RATE as u64) {
+            return;
+        }
+
+        let report = self.stats();
+        for (&id, player) in &self.players {
+            if player.watching_stats {
+                self.events.push(Envelope::to(
+                    id,
+                    StatsUpdated {
+                        report: report.clone(),
+                    },
+                ));
+            }
+        }
+    }
+
+    /// Resends the time now and then so clients do not drift.
+    fn sync_time(&mut self) {
+        if self
+            .tick
+            .is_multiple_of(self.config.time.sync_interval.max(1))
+        {
+            self.events.push(Envelope::broadcast(self.time_changed()));
+        }
+    }
+
+    fn teleport_player(&mut self, id: u32, pos: Vec3) {
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
+        };
+
+        hold_at(&mut self.physics, player, pos);
+        player.digging = None;
+
+        self.events.push(Envelope::broadcast(PlayerTeleported {
+            id,
+            pos: pos.to_array(),
+        }));
+    }
+
+    fn kick(&mut self, id: u32, reason: String) {
+        self.events.push(Envelope::to(id, Kicked { reason }));
+        self.remove_player(id);
+    }
+
+    fn set_game_mode(&mut self, id: u32, mode: GameMode) {
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
+        };
+        if player.game_mode == mode {
+            return;
+        }
+
+        player.game_mode = mode;
+        player.digging = None;
+
+        match mode {
+            GameMode::Survival => switch_movement(&mut self.physics, player, MovementMode::Walk),
+            GameMode::Creative => switch_movement(&mut self.physics, player, MovementMode::Fly),
+            GameMode::Spectator => {
+                // Spectators are moved directly, which needs a kinematic body.
+                switch_movement(&mut self.physics, player, MovementMode::Walk);
+                player.velocity = Vec3::ZERO;
+            }
+        }
+        self.physics
+            .set_collisions(player.body, mode != GameMode::Spectator);
+        self.physics.set_player_collisions(
+            player.body,
+            self.config.players.collisions && mode.collides_with_players(),
+        );
+
+        self.events
+            .push(Envelope::broadcast(GameModeChanged { id, mode }));
+    }
+
+    fn set_movement_mode(&mut self, id: u32, mode: MovementMode) {
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
+        };
+
+        let allowed = match player.game_mode {
+            GameMode::Survival => mode == MovementMode::Walk,
+            GameMode::Creative => true,
+            GameMode::Spectator => false,
+        };
+        if allowed {
+            switch_movement(&mut self.physics, player, mode);
+        }
+    }
+
+    fn set_view_distance(&mut self, id: u32, distance: i32) {
+        let distance = distance.clamp(1, self.max_view_distance());
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
+        };
+
+        if player.chunks.view_distance != distance {
+            player.chunks.view_distance = distance;
+            // Recomputed on the next chunk sync, which unloads and queues
+            // only what the new distance changes.
+            player.chunks.anchor = None;
+        }
+    }
+
+    /// The configured limit, but always at least the player's own chunk.
+    fn max_view_distance(&self) -> i32 {
+        self.config.streaming.max_view_distance.max(1)
+    }
+
+    fn in_reach(&self, id: u32, pos: IVec3) -> bool {
+        self.players.get(&id).is_some_and(|player| {
+            let center = pos.as_vec3() + Vec3::splat(0.5);
+            self.physics.position(player.body).distance(center) <= player.game_mode.reach()
+        })
+    }
+
+    fn break_block(&mut self, id: u32, pos: IVec3) {
+        let Some(voxel) = self.terrain.voxel(pos).filter(|v| v.is_solid()) else {
+            return;
+        };
+        if !self.in_reach(id, pos) {
+            return;
+        }
+
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
+        };
+        match player.game_mode {
+            GameMode::Survival => {
+                player.digging = Some(Digging {
+                    pos,
+                    remaining: (voxel.break_time() * TICK_RATE).ceil() as u32,
+                });
+            }
+            GameMode::Creative => self.remove_block(pos),
+            GameMode::Spectator => (),
+        }
+    }
+
+    /// Advances survival players' digging and breaks the blocks they
+    /// finish.
+    fn dig(&mut self) {
+        let mut broken = Vec::new();
+
+        for player in self.players.values_mut() {
+            let Some(digging) = &mut player.digging else {
+                continue;
+            };
+
+            let still_there = self
+                .terrain
+                .voxel(digging.pos)
+                .is_some_and(|v| v.is_solid());
+            if !player.input.dig || !still_there {
+                player.digging = None;
+                continue;
+            }
+
+            digging.remaining = digging.remaining.saturating_sub(1);
+            if digging.remaining == 0 {
+                broken.push(digging.pos);
+                player.digging = None;
+            }
+        }
+
+        for pos in broken {
+            let Some(voxel) = self.terrain.voxel(pos) else {
+                continue;
+            };
+            self.remove_block(pos);
+            self.items.spawn(
+                &mut self.physics,
+                &mut self.entities,
+                pos.as_vec3() + Vec3::splat(0.5),
+                ItemStack { voxel, count: 1 },
+            );
+        }
+    }
+
+    fn remove_block(&mut self, pos: IVec3) {
+        self.terrain.set_voxel(pos, Voxel::EMPTY);
+        self.fluids.activate_around(&self.terrain, pos);
+    }
+
+    /// Places the item in the player's selected slot, using it up unless
+    /// they are in creative. Creative players place from the palette when
+    /// the slot is empty.
+    fn place_block(&mut self, id: u32, pos: IVec3) {
+        let Some(player) = self.players.get(&id) else {
+            return;
+        };
+        let voxel = match (player.inventory.selected_item(), player.game_mode) {
+            (Some(stack), _) => stack.voxel,
+            (None, GameMode::Creative) => CREATIVE_PALETTE[player.inventory.selected()],
+            (None, _) => return,
+        };
+
+        if !player.game_mode.can_interact()
+            || !voxel.is_solid()
+            || !self.in_reach(id, pos)
+            || self.terrain.voxel(pos).is_none_or(|v| v.is_solid())
+        {
+            return;
+        }
+
+        // Never place a block inside a player.
+        let (min, max) = (pos.as_vec3(), pos.as_vec3() + Vec3::ONE);
+        let half = Vec3::new(PLAYER_RADIUS, PLAYER_HALF_HEIGHT, PLAYER_RADIUS);
+        let blocked = self.players.values().any(|player| {
+            let center = self.physics.position(player.body);
+            (center - half).cmplt(max).all() && (center + half).cmpgt(min).all()
+        });
+        if blocked {
+            return;
+        }
+
+        if let Some(player) = self.players.get_mut(&id)
+            && player.game_mode != GameMode::Creative
+        {
+            player.inventory.take_selected();
+        }
+
+        self.terrain.set_voxel(pos, voxel);
+        self.fluids.activate_around(&self.terrain, pos);
+    }
+
+    fn pick_up_items(&mut self) {
+        let pickups = self.items.update(
+            &mut self.physics,
+            &mut self.entities,
+            &mut self.players,
+            &self.config.items,
+        );
+        // Only players who were sent the item know what it is.
+        for Pickup { id, player } in pickups {
+            for (&viewer, state) in &self.players {
+                if state.entities.contains(&id) {
+                    self.events
+                        .push(Envelope::to(viewer, ItemPickedUp { id, player }));
+                }
+            }
+        }
+    }
+
+    fn sync_inventories(&mut self) {
+        for (&id, player) in &mut self.players {
+            if player.inventory.take_changed() {
+                self.events.push(Envelope::to(
+                    id,
+                    InventoryChanged {
+                        inventory: player.inventory.clone(),
+                    },
+                ));
+            }
         }
     }
 
     fn handle(&mut self, req: PendingRequest) {
         match req {
             PendingRequest::Connect(call) => {
-                let id = self.add_player(call.payload.name.clone());
-                call.reply(id);
+                let Connect {
+                    name,
+                    view_distance,
+                } = call.payload.clone();
+                let id = self.add_player(name, view_distance);
+                call.reply(Accepted {
+                    id,
+                    spawn: self.spawn.to_array(),
+                    max_view_distance: self.max_view_distance(),
+                });
+                self.events.push(Envelope::to(id, self.time_changed()));
 
                 for (pid, state) in &self.players {
                     if *pid == id {
@@ -138,6 +781,16 @@ impl VoxelWorld {
                             name: state.name.clone(),
                         },
                     ));
+
+                    if state.game_mode != GameMode::default() {
+                        self.events.push(Envelope::to(
+                            id,
+                            GameModeChanged {
+                                id: *pid,
+                                mode: state.game_mode,
+                            },
+                        ));
+                    }
                 }
             }
             PendingRequest::Ping(call) => {
@@ -146,7 +799,7 @@ impl VoxelWorld {
         }
     }
 
-    fn add_player(&mut self, name: String) -> u32 {
+    fn add_player(&mut self, name: String, view_distance: i32) -> u32 {
         let id = self.next_id;
         self.next_id += 1;
 
@@ -154,8 +807,19 @@ impl VoxelWorld {
             id,
             name: name.clone(),
         }));
-        let body = self.physics.add_body(Vec3::new(0.0, 60.0, 0.0));
-        self.players.insert(id, PlayerState::new(name, body));
+        let body = self.physics.add_character(self.spawn);
+        self.physics.set_enabled(body, false);
+        self.physics
+            .set_player_collisions(body, self.config.players.collisions);
+        self.players.insert(
+            id,
+            PlayerState::new(
+                name,
+                body,
+                ChunkInterest::new(view_distance.clamp(1, self.max_view_distance())),
+                ChunkStream::new(&self.config.streaming),
+            ),
+        );
 
         id
     }
@@ -163,6 +827,10 @@ impl VoxelWorld {
     fn remove_player(&mut self, id: u32) {
         if let Some(player) = self.players.remove(&id) {
             self.physics.remove_body(player.body);
+            for other in self.players.values_mut() {
+                other.in_view.remove(&id);
+                other.motion.remove(&id);
+            }
 
             let event = Envelope::broadcast(PlayerLeft {
                 id,
@@ -180,6 +848,7 @@ impl VoxelWorld {
             if player_state.chunks.anchor.replace(chunk_pos) == Some(chunk_pos) {
                 continue;
             }
+            let view_distance = player_state.chunks.view_distance;
 
             // unload
             let to_unload: Vec<_> = player_state
@@ -187,7 +856,7 @@ impl VoxelWorld {
                 .loaded
                 .iter()
                 .copied()
-                .filter(|&pos| !chunk_in_range(chunk_pos, pos, CHUNK_RENDER_DISTANCE))
+                .filter(|&pos| !chunk_in_range(chunk_pos, pos, view_distance))
                 .collect();
 
             for pos in to_unload {
@@ -200,30 +869,90 @@ impl VoxelWorld {
                 ));
             }
 
-            // load
-            let mut needed: Vec<_> = chunks_in_radius(chunk_pos, CHUNK_RENDER_DISTANCE)
-                .into_iter()
-                .filter(|pos| !player_state.chunks.loaded.contains(pos))
-                .collect();
-            needed.sort_by_key(|pos| pos.distance_squared(chunk_pos));
-
-            for pos in needed {
-                if let Some(data) = self.terrain.get(pos) {
-                    player_state.chunks.loaded.insert(pos);
-                    self.events.push(Envelope::to(
-                        player_id,
-                        ChunkLoaded {
-                            pos: pos.to_array(),
-                            data,
-                        },
-                    ));
-                } else {
+            // queue
+            let chunks = &mut player_state.chunks;
+            chunks
+                .queued
+                .retain(|&pos| chunk_in_range(chunk_pos, pos, view_distance));
+            for pos in chunks_in_radius(chunk_pos, view_distance) {
+                if chunks.loaded.contains(&pos) || !chunks.queued.insert(pos) {
+                    continue;
+                }
+                if self.terrain.get(pos).is_none() {
                     self.terrain.request(pos);
                 }
             }
         }
     }
 
+    /// Sends each player the queued chunks that are ready, best first, as
+    /// many as their stream allows.
+    fn stream_chunks(&mut self) {
+        for (&player_id, player) in &mut self.players {
+            let budget = player.stream.budget(self.tick, &self.config.streaming);
+            let Some(anchor) = player.chunks.anchor else {
+                continue;
+            };
+            if budget == 0 {
+                continue;
+            }
+
+            let look = Vec3::from_array(player.input.look);
+            let mut ready: Vec<_> = player
+                .chunks
+                .queued
+                .iter()
+                .copied()
+                .filter(|&pos| self.terrain.get(pos).is_some())
+                .map(|pos| (priority(anchor, look, pos), pos))
+                .collect();
+            if ready.len() > budget {
+                ready.select_nth_unstable_by(budget, |a, b| a.0.total_cmp(&b.0));
+                ready.truncate(budget);
+            }
+            // Ties are broken by position so the order never depends on
+            // the set's iteration order.
+            ready.sort_by(|a, b| {
+                a.0.total_cmp(&b.0)
+                    .then_with(|| a.1.to_array().cmp(&b.1.to_array()))
+            });
+
+            for &(_, pos) in &ready {
+                let Some(data) = self.terrain.get(pos) else {
+                    continue;
+                };
+                player.chunks.queued.remove(&pos);
+                player.chunks.loaded.insert(pos);
+                self.events.push(Envelope::to(
+                    player_id,
+                    ChunkLoaded {
+                        pos: pos.to_array(),
+                        data,
+                    },
+                ));
+            }
+            player.stream.sent(ready.len() as u32, self.tick);
+        }
+    }
+
+    fn sync_player_biomes(&mut self) {
+        for (&player_id, player_state) in self.players.iter_mut() {
+            let biome = self
+                .terrain
+                .biome_at(self.physics.position(player_state.body));
+
+            if player_state.biome == biome {
+                continue;
+            }
+
+            player_state.biome = biome;
+            if let Some(biome) = biome {
+                self.events
+                    .push(Envelope::to(player_id, BiomeChanged { biome }));
+            }
+        }
+    }
+
     fn sync_physics_chunks(&mut self) {
         let mut desired: HashSet<IVec3> = HashSet::new();
         for player in self.players.values() {
@@ -231,7 +960,15 @@ impl VoxelWorld {
             desired.extend(chunks_in_box(chunk, PHYSICS_RADIUS));
         }
 
-        for &pos in &desired {
+        // Nothing exists outside Y_RANGE, so there is nothing to collide with.
+        desired.retain(|pos| Y_RANGE.contains(&pos.y));
+
+        // Colliders are added and removed in a fixed order so two worlds
+        // fed the same input end up with identical physics.
+        let mut wanted: Vec<IVec3> = desired.iter().copied().collect();
+        wanted.sort_unstable_by_key(|pos| pos.to_array());
+
+        for pos in wanted {
             if self.physics.has_chunk(pos) {
                 continue;
             }
@@ -243,11 +980,12 @@ impl VoxelWorld {
             }
         }
 
-        let stale: Vec<IVec3> = self
+        let mut stale: Vec<IVec3> = self
             .physics
             .loaded_chunks()
             .filter(|pos| !desired.contains(pos))
             .collect();
+        stale.sort_unstable_by_key(|pos| pos.to_array());
 
         for pos in stale {
             self.physics.remove_chunk(pos);
@@ -256,9 +994,25 @@ impl VoxelWorld {
 
     fn poll_terrain(&mut self) {
         for (pos, data) in self.terrain.poll() {
+            if let Some(recorder) = &mut self.recorder {
+                recorder.generated(pos);
+            }
+            self.fluids.activate_chunk(&self.terrain, pos, &data);
+        }
+    }
+
+    /// Resends edited chunks and rebuilds their colliders. This runs before
+    /// the physics step so bodies never collide with voxels that are gone.
+    fn sync_modified_chunks(&mut self) {
+        // Sorted so colliders are rebuilt in the same order on every run.
+        let mut modified = self.terrain.take_modified();
+        modified.sort_unstable_by_key(|(pos, _)| pos.to_array());
+
+        for (pos, data) in modified {
+            self.terrain_hash = replay::fold_chunk(self.terrain_hash, pos, &data);
             for (&player_id, player) in &mut self.players {
-                if player.chunks.needs(pos) && !player.chunks.loaded.contains(&pos) {
-                    player.chunks.loaded.insert(pos);
+                if player.chunks.loaded.contains(&pos) {
+                    player.stream.sent(1, self.tick);
                     let event = Envelope::to(
                         player_id,
                         ChunkLoaded {
@@ -269,35 +1023,340 @@ impl VoxelWorld {
                     self.events.push(event);
                 }
             }
+
+            if self.physics.has_chunk(pos) {
+                self.physics.remove_chunk(pos);
+                self.physics.add_chunk(pos, &data);
+            }
         }
     }
 
-    fn process_player_inputs(&mut self) {
-        for player_state in self.players.values() {
-            let PlayerInput { dir, look, sprint } = player_state.input;
+    fn update_falling_blocks(&mut self) {
+        let mut waiting = Vec::new();
+        for pos in self.terrain.take_unsupported() {
+            // Without a collider below, the block would fall forever. It
+            // waits until the chunk below gets one.
+            let (below, _) = voxel_to_chunk_pos(pos - IVec3::Y);
+            if !self.physics.has_chunk(below) {
+                waiting.push(pos);
+                continue;
+            }
+
+            let Some(voxel) = self.terrain.voxel(pos) else {
+                continue;
+            };
+            self.falling.spawn(
+                &mut self.terrain,
+                &mut self.physics,
+                &mut self.entities,
+                pos,
+                voxel,
+            );
+            self.fluids.activate_around(&self.terrain, pos);
+        }
+        self.terrain.keep_unsupported(waiting);
+
+        let settled = self
+            .falling
+            .settle(&mut self.terrain, &mut self.physics, &mut self.entities);
+        for settled in settled {
+            if let Settled::Landed { pos } = settled {
+                self.fluids.activate_around(&self.terrain, pos);
+            }
+        }
+    }
+
+    fn respawn_fallen_players(&mut self) {
+        for (&player_id, player_state) in self.players.iter_mut() {
+            if self.physics.position(player_state.body).y >= KILL_HEIGHT {
+                continue;
+            }
+
+            self.events.push(Envelope::broadcast(PlayerDied {
+                id: player_id,
+                name: player_state.name.clone(),
+            }));
+
+            hold_at(&mut self.physics, player_state, self.spawn);
+
+            self.events.push(Envelope::broadcast(PlayerRespawned {
+                id: player_id,
+                pos: self.spawn.to_array(),
+            }));
+        }
+    }
+
+    /// Lets new and respawned players fall once every chunk around them is
+    /// generated. Generated chunks get their colliders in the same tick, so
+    /// by then the ground under them is solid.
+    fn release_held_players(&mut self) {
+        for player_state in self.players.values_mut() {
+            if !player_state.held {
+                continue;
+            }
+
+            let chunk = world_to_chunk_pos(self.physics.position(player_state.body));
+            let ready = chunks_in_box(chunk, 1)
+                .into_iter()
+                .filter(|pos| Y_RANGE.contains(&pos.y))
+                .all(|pos| self.terrain.get(pos).is_some());
+
+            if ready {
+                player_state.held = false;
+                self.physics.set_enabled(player_state.body, true);
+            }
+        }
+    }
+
+    /// Nudges players who stand too close apart. The character controller
+    /// stops them walking into each other; this is what lets them push.
+    fn push_players(&mut self, dt: f32) {
+        if !self.config.players.collisions {
+            return;
+        }
+
+        let ids: Vec<u32> = self
+            .players
+            .iter()
+            .filter(|(_, player)| !player.held)
+            .map(|(&id, _)| id)
+            .collect();
+
+        let mut pushes: HashMap<u32, Vec3> = HashMap::new();
+        for (i, &a) in ids.iter().enumerate() {
+            for &b in &ids[i + 1..] {
+                let push = self
+                    .physics
+                    .push(self.players[&a].body, self.players[&b].body);
+
+                if let Some(push) = push {
+                    *pushes.entry(a).or_default() += push;
+                    *pushes.entry(b).or_default() -= push;
+                }
+            }
+        }
+
+        for (id, push) in pushes {
+            let player = self.players.get_mut(&id).unwrap();
+            let dv = push * self.config.players.push_strength * dt;
+
+            match player.movement {
+                MovementMode::Walk => player.velocity += dv,
+                MovementMode::Fly => {
+                    let velocity = self.physics.velocity(player.body);
+                    self.physics.set_velocity(player.body, velocity + dv);
+                }
+            }
+        }
+    }
+
+    fn process_player_inputs(&mut self, dt: f32) {
+        for player_state in self.players.values_mut() {
+            if player_state.held {
+                continue;
+            }
+
+            let PlayerInput {
+                dir, look, sprint, ..
+            } = player_state.input;
             let dir = Vec3::from_array(dir);
             let look = Vec3::from_array(look);
 
             let forward = Vec3::new(look.x, 0.0, look.z).normalize_or_zero();
             let right = forward.cross(Vec3::Y);
-            let move_dir = forward * dir.x + right * dir.z + Vec3::Y * dir.y;
-
             let speed_mult = if sprint { SPRINT_MULTIPLIER } else { 1.0 };
-            let force = move_dir * THRUST * speed_mult;
 
-            self.physics.set_force(player_state.body, force);
+            if player_state.game_mode == GameMode::Spectator {
+                let move_dir = forward * dir.x + right * dir.z + Vec3::Y * dir.y;
+                let delta = move_dir.normalize_or_zero() * SPECTATOR_SPEED * speed_mult * dt;
+                self.physics.translate(player_state.body, delta);
+                continue;
+            }
+
+            match player_state.movement {
+                MovementMode::Walk => {
+                    let wish = (forward * dir.x + right * dir.z).normalize_or_zero()
+                        * MOVEMENT_SPEED
+                        * speed_mult;
+                    let pos = self.physics.position(player_state.body);
+                    let (submerged, medium) = fluid::submersion(&self.terrain, pos);
+
+                    let velocity = controller::walk(
+                        player_state.velocity,
+                        wish,
+                        dir.y > 0.0,
+                        player_state.grounded,
+                        submerged,
+                        medium,
+                        dt,
+                    );
+                    let desired = velocity * dt;
+                    let (moved, grounded) =
+                        self.physics.move_character(player_state.body, desired, dt);
+
+                    player_state.velocity =
+                        controller::clip(velocity, desired, moved, grounded, dt);
+                    player_state.grounded = grounded;
+                }
+                MovementMode::Fly => {
+                    let move_dir = forward * dir.x + right * dir.z + Vec3::Y * dir.y;
+                    let force = move_dir * THRUST * speed_mult;
+
+                    self.physics.set_force(player_state.body, force);
+                }
+            }
         }
     }
 
+    /// Buoyancy and drag for flying players. Walking players get theirs
+    /// from the character controller.
+    fn apply_fluid_forces(&mut self) {
+        for player_state in self.players.values() {
+            if player_state.movement != MovementMode::Fly {
+                continue;
+            }
+
+            let pos = self.physics.position(player_state.body);
+            let (submerged, (density, drag)) = fluid::submersion(&self.terrain, pos);
+            self.physics
+                .apply_fluid(player_state.body, submerged, density, drag);
+        }
+    }
+
+    /// Sends every player their own movement, and the movement of the
+    /// visible players in their loaded chunks. Players coming into or going
+    /// out of that range are announced with enter and exit events. Movement
+    /// the client already has is skipped.
     fn broadcast_movement(&mut self) {
-        for (player_id, player_state) in &self.players {
-            let event = Envelope::broadcast(PlayerMoved {
-                tick: self.tick,
-                id: *player_id,
-                pos: self.physics.position(player_state.body).to_array(),
-                look: player_state.input.look,
-            });
-            self.events.push(event);
+        let states: Vec<(u32, Vec3, MotionState, bool)> = self
+            .players
+            .iter()
+            .map(|(&id, player)| {
+                let pos = self.physics.position(player.body);
+                let state = MotionState::new(pos, Vec3::from_array(player.input.look));
+                (id, pos, state, player.game_mode.is_visible())
+            })
+            .collect();
+
+        for (&viewer_id, viewer) in &mut self.players {
+            for &(id, pos, state, visible) in &states {
+                if id != viewer_id {
+                    let sees = visible && viewer.chunks.sees(pos);
+                    let known = viewer.in_view.contains(&id);
+
+                    match (sees, known) {
+                        (true, false) => {
+                            viewer.in_view.insert(id);
+                            self.events.push(Envelope::to(
+                                viewer_id,
+                                PlayerEnteredView {
+                                    id,
+                                    pos: pos.to_array(),
+                                },
+                            ));
+                        }
+                        (false, true) => {
+                            viewer.in_view.remove(&id);
+                            viewer.motion.remove(&id);
+                            self.events
+                                .push(Envelope::to(viewer_id, PlayerExitedView { id }));
+                            continue;
+                        }
+                        (false, false) => continue,
+                        (true, true) => {}
+                    }
+                }
+
+                let sync = viewer.motion.entry(id).or_default();
+                if let Some(motion) = sync.encode(self.tick, state) {
+                    let moved = PlayerMoved {
+                        tick: self.tick,
+                        id,
+                        motion,
+                    };
+                    self.events.push(Envelope::to(viewer_id, moved));
+                }
+            }
+        }
+    }
+
+    /// Tells each player about the entities in their loaded chunks: spawns
+    /// for ones they have not seen, updates for ones that changed and
+    /// despawns for ones that are gone or out of view.
+    fn replicate_entities(&mut self) {
+        let changes = self.entities.take_changes();
+        let despawned = self.entities.take_despawned();
+
+        for (&player_id, player) in &mut self.players {
+            for id in &despawned {
+                if player.entities.remove(id) {
+                    self.events
+                        .push(Envelope::to(player_id, EntityDespawned { id: *id }));
+                }
+            }
+
+            for (id, entity) in self.entities.iter() {
+                let sees = player.chunks.sees(entity.pos());
+                let knows = player.entities.contains(&id);
+
+                let event: WorldEvent = match (sees, knows) {
+                    (true, false) => {
+                        player.entities.insert(id);
+                        EntitySpawned {
+                            tick: self.tick,
+                            id,
+                            components: entity.components(),
+                        }
+                        .into()
+                    }
+                    (false, true) => {
+                        player.entities.remove(&id);
+                        EntityDespawned { id }.into()
+                    }
+                    (true, true) => match changes.get(&id) {
+                        Some(components) => EntityUpdated {
+                            tick: self.tick,
+                            id,
+                            components: components.clone(),
+                        }
+                        .into(),
+                        None => continue,
+                    },
+                    (false, false) => continue,
+                };
+                self.events.push(Envelope::to(player_id, event));
+            }
         }
     }
 }
+
+/// Moves a player and keeps them there until the chunks around them are
+/// ready.
+fn hold_at(physics: &mut Physics, player: &mut PlayerState, pos: Vec3) {
+    physics.teleport(player.body, pos);
+    physics.set_enabled(player.body, false);
+    player.held = true;
+    player.velocity = Vec3::ZERO;
+}
+
+/// Swaps how a player's body is driven, handing the velocity over so the
+/// player keeps their momentum.
+fn switch_movement(physics: &mut Physics, player: &mut PlayerState, mode: MovementMode) {
+    if player.movement == mode {
+        return;
+    }
+
+    match mode {
+        MovementMode::Walk => {
+            player.velocity = physics.velocity(player.body);
+            physics.set_kinematic(player.body, true);
+        }
+        MovementMode::Fly => {
+            physics.set_kinematic(player.body, false);
+            physics.set_velocity(player.body, player.velocity);
+        }
+    }
+
+    player.movement = mode;
+    player.grounded = false;
+}
@@ -0,0 +1,154 @@
+//! Compact encoding of player movement.
+//!
+//! Positions and look directions are quantized, and the world sends each
+//! player's motion as a delta against the last state the receiving client
+//! acknowledged. States the client already has are not sent at all, so a
+//! player standing still costs nothing once their last position arrived.
+
+use std::collections::VecDeque;
+
+use glam::{IVec3, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// Position steps per block.
+pub const POSITION_SCALE: f32 = 256.0;
+/// A delta is never taken against a state older than this many ticks; a
+/// keyframe is sent instead. Also how far back clients keep states around
+/// to decode deltas.
+pub const KEYFRAME_INTERVAL: u64 = 60;
+
+/// A player's position and look direction, as sent over the wire.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MotionState {
+    pub pos: [i32; 3],
+    /// Unit vector, each component scaled to the `i16` range.
+    pub look: [i16; 3],
+}
+
+impl MotionState {
+    pub fn new(pos: Vec3, look: Vec3) -> Self {
+        let look = look.normalize_or_zero() * i16::MAX as f32;
+        Self {
+            pos: (pos * POSITION_SCALE).round().as_ivec3().to_array(),
+            look: look.to_array().map(|v| v.round() as i16),
+        }
+    }
+
+    pub fn pos(&self) -> Vec3 {
+        Vec3::from_array(self.pos.map(|v| v as f32)) / POSITION_SCALE
+    }
+
+    pub fn look(&self) -> Vec3 {
+        Vec3::from_array(self.look.map(|v| v as f32)) / i16::MAX as f32
+    }
+
+    /// The difference to `base`, if the position moved little enough to fit.
+    fn delta_from(&self, base: &MotionState) -> Option<([i16; 3], [i16; 3])> {
+        let diff = IVec3::from_array(self.pos) - IVec3::from_array(base.pos);
+        let pos = diff.to_array().map(i16::try_from);
+        let pos = [pos[0].ok()?, pos[1].ok()?, pos[2].ok()?];
+        let look = std::array::from_fn(|i| self.look[i].wrapping_sub(base.look[i]));
+        Some((pos, look))
+    }
+
+    fn apply(&self, pos: [i16; 3], look: [i16; 3]) -> Self {
+        Self {
+            pos: std::array::from_fn(|i| self.pos[i] + pos[i] as i32),
+            look: std::array::from_fn(|i| self.look[i].wrapping_add(look[i])),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Motion {
+    /// The full state, decodable on its own.
+    Keyframe(MotionState),
+    /// The change since the state the client acknowledged for tick `base`.
+    Delta {
+        base: u64,
+        pos: [i16; 3],
+        look: [i16; 3],
+    },
+}
+
+/// What the world knows one client has of one player's motion.
+#[derive(Default)]
+pub struct MotionSync {
+    /// States sent since the last acknowledged one, oldest first.
+    sent: VecDeque<(u64, MotionState)>,
+    acked: Option<(u64, MotionState)>,
+}
+
+impl MotionSync {
+    /// The motion to send for `state`, or `None` when the client already
+    /// has it. A state equal to the acknowledged one is still sent if a
+    /// different one went out since, as the client may be showing that.
+    pub fn encode(&mut self, tick: u64, state: MotionState) -> Option<Motion> {
+        let settled = self.sent.back().is_none_or(|&(_, sent)| sent == state);
+        let delta = match self.acked {
+            Some((_, acked)) if acked == state && settled => return None,
+            Some((base, acked)) if tick - base < KEYFRAME_INTERVAL => state
+                .delta_from(&acked)
+                .map(|(pos, look)| Motion::Delta { base, pos, look }),
+            _ => None,
+        };
+
+        self.sent.push_back((tick, state));
+        while self
+            .sent
+            .front()
+            .is_some_and(|&(sent, _)| tick - sent >= KEYFRAME_INTERVAL)
+        {
+            self.sent.pop_front();
+        }
+
+        Some(delta.unwrap_or(Motion::Keyframe(state)))
+    }
+
+    /// Marks the state sent on `tick` as received. Acks for states that
+    /// were not sent or are older than the current base are ignored.
+    pub fn ack(&mut self, tick: u64) {
+        let Some(&acked) = self.sent.iter().find(|&&(sent, _)| sent == tick) else {
+            return;
+        };
+        self.acked = Some(acked);
+        self.sent.retain(|&(sent, _)| sent > tick);
+    }
+}
+
+/// The states a client received for one player, for decoding deltas.
+#[derive(Default)]
+pub struct MotionHistory {
+    states: VecDeque<(u64, MotionState)>,
+}
+
+impl MotionHistory {
+    /// Decodes a received motion. Returns `None` if it is a delta against
+    /// a state this history no longer has, which the next keyframe fixes.
+    pub fn receive(&mut self, tick: u64, motion: Motion) -> Option<MotionState> {
+        let state = match motion {
+            Motion::Keyframe(state) => state,
+            Motion::Delta { base, pos, look } => {
+                let &(_, base) = self.states.iter().find(|&&(t, _)| t == base)?;
+                base.apply(pos, look)
+            }
+        };
+
+        // Datagrams can arrive out of order; keep the history sorted.
+        let at = self.states.partition_point(|&(t, _)| t < tick);
+        if self.states.get(at).is_none_or(|&(t, _)| t != tick) {
+            self.states.insert(at, (tick, state));
+        }
+
+        let newest = self.states.back().map_or(tick, |&(t, _)| t);
+        while self
+            .states
+            .front()
+            .is_some_and(|&(t, _)| newest - t > 2 * KEYFRAME_INTERVAL)
+        {
+            self.states.pop_front();
+        }
+
+        Some(state)
+    }
+}
@@ -1,12 +1,27 @@
 use glam::IVec3;
-use rapier3d::prelude::*;
+use rapier3d::{
+    control::{CharacterAutostep, CharacterLength, KinematicCharacterController},
+    prelude::*,
+};
 use std::collections::HashMap;
 use voxel_core::{
     VoxelBuffer,
     mesh::{Mesher, block::BlockMesher},
 };
 
-use crate::terrain::CHUNK_SIZE;
+use crate::{FLIGHT_GRAVITY, GRAVITY, PLAYER_HALF_HEIGHT, PLAYER_RADIUS, terrain::CHUNK_SIZE};
+
+/// Tallest ledge a walking character climbs without jumping.
+const STEP_HEIGHT: f32 = 1.05;
+/// Steepest slope, in degrees, a walking character can climb.
+const MAX_SLOPE: f32 = 50.0;
+/// Gap between two players within which they start pushing each other.
+const PUSH_MARGIN: f32 = 0.2;
+
+const TERRAIN_GROUP: Group = Group::GROUP_1;
+const PLAYER_GROUP: Group = Group::GROUP_2;
+const BLOCK_GROUP: Group = Group::GROUP_3;
+const ITEM_GROUP: Group = Group::GROUP_4;
 
 pub type BodyHandle = RigidBodyHandle;
 
@@ -20,9 +35,11 @@ pub struct Physics {
     multibody_joint_set: MultibodyJointSet,
     rigid_body_set: RigidBodySet,
     collider_set: ColliderSet,
-    chunk_colliders: HashMap<IVec3, ColliderHandle>,
+    /// `None` for chunks with nothing solid, so they are not meshed again.
+    chunk_colliders: HashMap<IVec3, Option<ColliderHandle>>,
 
     // Machinery
+    character_controller: KinematicCharacterController,
     physics_pipeline: PhysicsPipeline,
     island_manager: IslandManager,
     broad_phase: DefaultBroadPhase,
@@ -34,6 +51,23 @@ pub struct Physics {
     event_handler: (),
 }
 
+fn groups(memberships: Group, filter: Group) -> InteractionGroups {
+    InteractionGroups {
+        memberships,
+        filter,
+        ..InteractionGroups::all()
+    }
+}
+
+fn player_groups(collide_with_players: bool) -> InteractionGroups {
+    let filter = if collide_with_players {
+        Group::ALL
+    } else {
+        Group::ALL.difference(PLAYER_GROUP)
+    };
+    groups(PLAYER_GROUP, filter)
+}
+
 impl Physics {
     pub fn init() -> Self {
         Self::empty()
@@ -41,13 +75,24 @@ impl Physics {
 
     fn empty() -> Self {
         Self {
-            gravity: Vec3::new(0.0, -9.81, 0.0),
+            gravity: Vec3::new(0.0, -GRAVITY, 0.0),
             integration_parameters: IntegrationParameters::default(),
             impulse_joint_set: ImpulseJointSet::new(),
             multibody_joint_set: MultibodyJointSet::new(),
             rigid_body_set: RigidBodySet::new(),
             collider_set: ColliderSet::new(),
             chunk_colliders: HashMap::new(),
+            character_controller: KinematicCharacterController {
+                autostep: Some(CharacterAutostep {
+                    max_height: CharacterLength::Absolute(STEP_HEIGHT),
+                    min_width: CharacterLength::Absolute(0.2),
+                    include_dynamic_bodies: false,
+                }),
+                max_slope_climb_angle: MAX_SLOPE.to_radians(),
+                min_slope_slide_angle: MAX_SLOPE.to_radians(),
+                snap_to_ground: Some(CharacterLength::Absolute(0.2)),
+                ..Default::default()
+            },
             physics_pipeline: PhysicsPipeline::new(),
             island_manager: IslandManager::new(),
             broad_phase: DefaultBroadPhase::new(),
@@ -82,10 +127,18 @@ impl Physics {
         body.add_force(force, true);
     }
 
-    pub fn add_body(&mut self, pos: Vec3) -> BodyHandle {
-        let body = RigidBodyBuilder::dynamic().translation(pos).build();
+    /// An upright capsule moved by `move_character`. It can be switched to
+    /// a dynamic body pushed by forces with `set_kinematic`.
+    pub fn add_character(&mut self, pos: Vec3) -> BodyHandle {
+        let body = RigidBodyBuilder::kinematic_position_based()
+            .translation(pos)
+            .lock_rotations()
+            .build();
         let handle = self.rigid_body_set.insert(body);
-        let collider = ColliderBuilder::ball(0.5).build();
+        let collider =
+            ColliderBuilder::capsule_y(PLAYER_HALF_HEIGHT - PLAYER_RADIUS, PLAYER_RADIUS)
+                .collision_groups(player_groups(true))
+                .build();
 
         self.collider_set
             .insert_with_parent(collider, handle, &mut self.rigid_body_set);
@@ -93,6 +146,179 @@ impl Physics {
         handle
     }
 
+    pub fn set_kinematic(&mut self, handle: BodyHandle, kinematic: bool) {
+        let body_type = if kinematic {
+            RigidBodyType::KinematicPositionBased
+        } else {
+            RigidBodyType::Dynamic
+        };
+        let body = &mut self.rigid_body_set[handle];
+        body.set_body_type(body_type, true);
+        // Flying characters are dynamic but feel less gravity, so thrust
+        // can lift them.
+        body.set_gravity_scale(FLIGHT_GRAVITY / GRAVITY, true);
+    }
+
+    /// Moves a kinematic body straight through anything in its way.
+    pub fn translate(&mut self, handle: BodyHandle, delta: Vec3) {
+        let body = &mut self.rigid_body_set[handle];
+        body.set_next_kinematic_translation(body.translation() + delta);
+    }
+
+    /// Whether a body's colliders touch anything at all.
+    pub fn set_collisions(&mut self, handle: BodyHandle, enabled: bool) {
+        for &collider in self.rigid_body_set[handle].colliders() {
+            self.collider_set[collider].set_enabled(enabled);
+        }
+    }
+
+    /// Whether a character bumps into other characters.
+    pub fn set_player_collisions(&mut self, handle: BodyHandle, enabled: bool) {
+        for &collider in self.rigid_body_set[handle].colliders() {
+            self.collider_set[collider].set_collision_groups(player_groups(enabled));
+        }
+    }
+
+    /// How hard character `a` is pushed away from character `b`, from zero
+    /// when they are `PUSH_MARGIN` apart to one when their centers meet.
+    /// `None` when they are not close enough or do not collide.
+    pub fn push(&self, a: BodyHandle, b: BodyHandle) -> Option<Vec3> {
+        let [body_a, body_b] = [a, b].map(|handle| &self.rigid_body_set[handle]);
+        let collides = match (body_a.colliders().first(), body_b.colliders().first()) {
+            (Some(&ca), Some(&cb)) => {
+                let (ca, cb) = (&self.collider_set[ca], &self.collider_set[cb]);
+                ca.is_enabled()
+                    && cb.is_enabled()
+                    && ca.collision_groups().test(cb.collision_groups())
+            }
+            _ => false,
+        };
+        if !collides {
+            return None;
+        }
+
+        let delta = body_a.translation() - body_b.translation();
+        if delta.y.abs() >= 2.0 * PLAYER_HALF_HEIGHT {
+            return None;
+        }
+
+        let reach = 2.0 * PLAYER_RADIUS + PUSH_MARGIN;
+        let horizontal = delta.with_y(0.0);
+        let distance = horizontal.length();
+        if distance >= reach {
+            return None;
+        }
+
+        let dir = horizontal.try_normalize().unwrap_or(Vec3::X);
+        Some(dir * (1.0 - distance / reach))
+    }
+
+    /// Moves a kinematic character by up to `desired` over the next step,
+    /// sliding along walls and stepping up ledges. Returns how far it will
+    /// actually move and whether it ends up on the ground.
+    pub fn move_character(&mut self, handle: BodyHandle, desired: Vec3, dt: f32) -> (Vec3, bool) {
+        let body = &self.rigid_body_set[handle];
+        let Some(&collider) = body.colliders().first() else {
+            return (Vec3::ZERO, false);
+        };
+
+        let filter = QueryFilter::default()
+            .exclude_rigid_body(handle)
+            .groups(self.collider_set[collider].collision_groups());
+        let queries = self.broad_phase.as_query_pipeline(
+            self.narrow_phase.query_dispatcher(),
+            &self.rigid_body_set,
+            &self.collider_set,
+            filter,
+        );
+        let movement = self.character_controller.move_shape(
+            dt,
+            &queries,
+            self.collider_set[collider].shape(),
+            body.position(),
+            desired,
+            |_| {},
+        );
+
+        let body = &mut self.rigid_body_set[handle];
+        body.set_next_kinematic_translation(body.translation() + movement.translation);
+
+        (movement.translation, movement.grounded)
+    }
+
+    /// Pushes a body up and slows it down in proportion to how much of it is
+    /// submerged. `density` is relative to the body, so above one floats.
+    pub fn apply_fluid(&mut self, handle: BodyHandle, submerged: f32, density: f32, drag: f32) {
+        let body = &mut self.rigid_body_set[handle];
+        let buoyancy = -self.gravity * body.gravity_scale() * body.mass() * density * submerged;
+        body.add_force(buoyancy, true);
+        body.set_linear_damping(drag * submerged);
+    }
+
+    /// A unit cube centered on `pos` that falls straight down.
+    pub fn add_block_body(&mut self, pos: Vec3) -> BodyHandle {
+        let body = RigidBodyBuilder::dynamic()
+            .translation(pos)
+            .lock_rotations()
+            .ccd_enabled(true)
+            .build();
+        let handle = self.rigid_body_set.insert(body);
+        let collider = ColliderBuilder::cuboid(0.45, 0.45, 0.45)
+            .collision_groups(groups(BLOCK_GROUP, Group::ALL))
+            .build();
+
+        self.collider_set
+            .insert_with_parent(collider, handle, &mut self.rigid_body_set);
+
+        handle
+    }
+
+    /// A small bouncy body for a dropped item. Items only collide with the
+    /// terrain and falling blocks, never with players or each other.
+    pub fn add_item_body(&mut self, pos: Vec3, velocity: Vec3) -> BodyHandle {
+        let body = RigidBodyBuilder::dynamic()
+            .translation(pos)
+            .linvel(velocity)
+            .lock_rotations()
+            .linear_damping(0.5)
+            .ccd_enabled(true)
+            .build();
+        let handle = self.rigid_body_set.insert(body);
+        let collider = ColliderBuilder::ball(0.125)
+            .restitution(0.4)
+            .friction(0.8)
+            .collision_groups(groups(ITEM_GROUP, TERRAIN_GROUP | BLOCK_GROUP))
+            .build();
+
+        self.collider_set
+            .insert_with_parent(collider, handle, &mut self.rigid_body_set);
+
+        handle
+    }
+
+    /// Moves a body and brings it to a full stop.
+    pub fn teleport(&mut self, handle: BodyHandle, pos: Vec3) {
+        let body = &mut self.rigid_body_set[handle];
+        body.set_translation(pos, true);
+        body.set_linvel(Vec3::ZERO, true);
+        body.set_angvel(Vec3::ZERO, true);
+        body.reset_forces(true);
+    }
+
+    /// Disabled bodies keep their position but take no part in the
+    /// simulation.
+    pub fn set_enabled(&mut self, handle: BodyHandle, enabled: bool) {
+        self.rigid_body_set[handle].set_enabled(enabled);
+    }
+
+    pub fn velocity(&self, handle: BodyHandle) -> Vec3 {
+        self.rigid_body_set[handle].linvel()
+    }
+
+    pub fn set_velocity(&mut self, handle: BodyHandle, velocity: Vec3) {
+        self.rigid_body_set[handle].set_linvel(velocity, true);
+    }
+
     pub fn position(&self, handle: BodyHandle) -> Vec3 {
         self.rigid_body_set[handle].translation()
     }
@@ -113,7 +339,8 @@ impl Physics {
             return;
         }
 
-        let Some(mesh) = BlockMesher.generate(buffer) else {
+        let Some(mesh) = BlockMesher.generate(&buffer.solids()) else {
+            self.chunk_colliders.insert(chunk_pos, None);
             return;
         };
 
@@ -132,19 +359,23 @@ impl Physics {
         let origin = (chunk_pos * CHUNK_SIZE.as_ivec3()).as_vec3();
 
         let collider = match ColliderBuilder::trimesh(vertices, indices) {
-            Ok(builder) => builder.translation(origin).build(),
+            Ok(builder) => builder
+                .translation(origin)
+                .collision_groups(groups(TERRAIN_GROUP, Group::ALL))
+                .build(),
             Err(err) => {
                 eprintln!("skipping trimesh collider for chunk {chunk_pos:?}: {err:?}");
+                self.chunk_colliders.insert(chunk_pos, None);
                 return;
             }
         };
 
         let handle = self.collider_set.insert(collider);
-        self.chunk_colliders.insert(chunk_pos, handle);
+        self.chunk_colliders.insert(chunk_pos, Some(handle));
     }
 
     pub fn remove_chunk(&mut self, chunk_pos: IVec3) {
-        if let Some(handle) = self.chunk_colliders.remove(&chunk_pos) {
+        if let Some(Some(handle)) = self.chunk_colliders.remove(&chunk_pos) {
             self.collider_set.remove(
                 handle,
                 &mut self.island_manager,
@@ -162,3 +393,112 @@ impl Physics {
         self.chunk_colliders.keys().copied()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1.0 / 60.0;
+
+    /// Two walking characters on the x axis, `gap` apart between their
+    /// surfaces, with `a` at the origin.
+    fn two_characters(gap: f32) -> (Physics, BodyHandle, BodyHandle) {
+        let mut physics = Physics::init();
+        let a = physics.add_character(Vec3::ZERO);
+        let b = physics.add_character(Vec3::X * (2.0 * PLAYER_RADIUS + gap));
+        // Queries only see bodies the broad phase has picked up.
+        physics.step(DT);
+        (physics, a, b)
+    }
+
+    fn walk(physics: &mut Physics, handle: BodyHandle, velocity: Vec3, ticks: u32) {
+        for _ in 0..ticks {
+            physics.move_character(handle, velocity * DT, DT);
+            physics.step(DT);
+        }
+    }
+
+    #[test]
+    fn walking_into_a_player_stops_at_them() {
+        let (mut physics, a, b) = two_characters(2.0);
+
+        walk(&mut physics, a, Vec3::X * 5.0, 60);
+
+        let gap = physics.position(b).x - physics.position(a).x;
+        assert!(gap >= 2.0 * PLAYER_RADIUS - 0.05, "overlapped, gap {gap}");
+        assert!(gap < 2.0 * PLAYER_RADIUS + 0.1, "never reached, gap {gap}");
+    }
+
+    #[test]
+    fn walking_through_a_player_with_collisions_off() {
+        let (mut physics, a, b) = two_characters(2.0);
+        physics.set_player_collisions(a, false);
+
+        walk(&mut physics, a, Vec3::X * 5.0, 60);
+
+        assert!(physics.position(a).x > physics.position(b).x);
+    }
+
+    #[test]
+    fn one_side_turning_collisions_off_is_enough() {
+        let (mut physics, a, b) = two_characters(2.0);
+        physics.set_player_collisions(b, false);
+
+        walk(&mut physics, a, Vec3::X * 5.0, 60);
+
+        assert!(physics.position(a).x > physics.position(b).x);
+    }
+
+    #[test]
+    fn touching_players_push_each_other_apart() {
+        let (physics, a, b) = two_characters(0.05);
+
+        let push_a = physics.push(a, b).expect("a should be pushed");
+        let push_b = physics.push(b, a).expect("b should be pushed");
+        assert!(push_a.x < 0.0 && push_b.x > 0.0);
+        assert_eq!(push_a.y, 0.0);
+        assert!((push_a + push_b).length() < 1e-6);
+    }
+
+    #[test]
+    fn distant_players_do_not_push() {
+        let (physics, a, b) = two_characters(1.0);
+        assert!(physics.push(a, b).is_none());
+    }
+
+    #[test]
+    fn players_without_collisions_do_not_push() {
+        let (mut physics, a, b) = two_characters(0.05);
+        physics.set_player_collisions(a, false);
+        assert!(physics.push(a, b).is_none());
+        assert!(physics.push(b, a).is_none());
+    }
+
+    #[test]
+    fn empty_chunks_are_tracked_without_a_collider() {
+        let mut physics = Physics::init();
+        let empty = VoxelBuffer::new(CHUNK_SIZE.to_array());
+
+        physics.add_chunk(IVec3::ZERO, &empty);
+        assert!(physics.has_chunk(IVec3::ZERO));
+        assert_eq!(physics.collider_set.len(), 0);
+
+        physics.remove_chunk(IVec3::ZERO);
+        assert!(!physics.has_chunk(IVec3::ZERO));
+    }
+
+    #[test]
+    fn flying_players_bounce_off_each_other() {
+        let (mut physics, a, b) = two_characters(2.0);
+        for (handle, velocity) in [(a, Vec3::X * 5.0), (b, Vec3::NEG_X * 5.0)] {
+            physics.set_kinematic(handle, false);
+            physics.set_velocity(handle, velocity);
+        }
+
+        for _ in 0..60 {
+            physics.step(DT);
+            let gap = (physics.position(b) - physics.position(a)).length();
+            assert!(gap >= 2.0 * PLAYER_RADIUS - 0.1, "overlapped, gap {gap}");
+        }
+    }
+}
@@ -1,11 +1,17 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use glam::IVec3;
+use glam::{IVec3, Vec3};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    chat::ChatLimiter,
+    entity::EntityId,
+    generator::Biome,
+    inventory::Inventory,
+    movement::MotionSync,
     physics::BodyHandle,
-    terrain::{CHUNK_RENDER_DISTANCE, chunk_in_range},
+    streaming::ChunkStream,
+    terrain::{chunk_in_range, world_to_chunk_pos},
 };
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone)]
@@ -13,37 +19,149 @@ pub struct PlayerInput {
     pub dir: [f32; 3],
     pub look: [f32; 3],
     pub sprint: bool,
+    /// Whether the player is holding down break on the block they started
+    /// digging with `BreakBlock`.
+    pub dig: bool,
+}
+
+/// A block a survival player is partway through breaking.
+pub struct Digging {
+    pub pos: IVec3,
+    /// Ticks of digging left.
+    pub remaining: u32,
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    /// Walks under gravity and digs blocks by hand within reach.
+    #[default]
+    Survival,
+    /// May fly, breaks blocks instantly and reaches further.
+    Creative,
+    /// Flies through terrain, cannot touch the world and is hidden from
+    /// other players.
+    Spectator,
+}
+
+impl GameMode {
+    pub fn can_fly(self) -> bool {
+        self != Self::Survival
+    }
+
+    pub fn can_interact(self) -> bool {
+        self != Self::Spectator
+    }
+
+    pub fn is_visible(self) -> bool {
+        self != Self::Spectator
+    }
+
+    pub fn collides_with_players(self) -> bool {
+        self != Self::Spectator
+    }
+
+    /// How far from the player blocks can be broken and placed.
+    pub fn reach(self) -> f32 {
+        match self {
+            Self::Survival => 6.0,
+            Self::Creative => 12.0,
+            Self::Spectator => 0.0,
+        }
+    }
+}
+
+/// How a player's body is driven.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MovementMode {
+    /// Walks on the ground under gravity and jumps.
+    #[default]
+    Walk,
+    /// Thrusts freely in every direction.
+    Fly,
 }
 
-#[derive(Default)]
 pub struct ChunkInterest {
     pub anchor: Option<IVec3>,
+    /// How far from the anchor, in chunks, the player is sent chunks.
+    pub view_distance: i32,
     pub loaded: HashSet<IVec3>,
+    /// Chunks in range that have not been sent yet.
+    pub queued: HashSet<IVec3>,
 }
 
 impl ChunkInterest {
+    pub fn new(view_distance: i32) -> Self {
+        Self {
+            anchor: None,
+            view_distance,
+            loaded: HashSet::new(),
+            queued: HashSet::new(),
+        }
+    }
+
     pub fn needs(&self, chunk_pos: IVec3) -> bool {
         match self.anchor {
-            Some(anchor) => chunk_in_range(anchor, chunk_pos, CHUNK_RENDER_DISTANCE),
+            Some(anchor) => chunk_in_range(anchor, chunk_pos, self.view_distance),
             None => false,
         }
     }
+
+    /// Whether something at `pos` is in a chunk the player has loaded.
+    pub fn sees(&self, pos: Vec3) -> bool {
+        self.loaded.contains(&world_to_chunk_pos(pos))
+    }
 }
 
 pub struct PlayerState {
     pub input: PlayerInput,
     pub chunks: ChunkInterest,
+    pub stream: ChunkStream,
     pub name: String,
     pub body: BodyHandle,
+    pub biome: Option<Biome>,
+    /// Kept in place until the ground around the player has colliders.
+    pub held: bool,
+    pub game_mode: GameMode,
+    pub movement: MovementMode,
+    /// Velocity of a walking player; flying players keep theirs in the
+    /// physics body.
+    pub velocity: Vec3,
+    pub grounded: bool,
+    pub digging: Option<Digging>,
+    pub inventory: Inventory,
+    pub chat: ChatLimiter,
+    /// Whether the player wants tick timing, e.g. for a debug overlay.
+    pub watching_stats: bool,
+    /// Entities this player has been told about.
+    pub entities: HashSet<EntityId>,
+    /// Other players whose movement this player is being sent.
+    pub in_view: HashSet<u32>,
+    /// What this player's client has of each player's movement, its own
+    /// included.
+    pub motion: HashMap<u32, MotionSync>,
 }
 
 impl PlayerState {
-    pub fn new(name: String, body: BodyHandle) -> Self {
+    pub fn new(name: String, body: BodyHandle, chunks: ChunkInterest, stream: ChunkStream) -> Self {
         Self {
             input: PlayerInput::default(),
-            chunks: ChunkInterest::default(),
+            chunks,
+            stream,
             name,
             body,
+            biome: None,
+            held: true,
+            game_mode: GameMode::default(),
+            movement: MovementMode::default(),
+            velocity: Vec3::ZERO,
+            grounded: false,
+            digging: None,
+            inventory: Inventory::default(),
+            chat: ChatLimiter::default(),
+            watching_stats: false,
+            entities: HashSet::new(),
+            in_view: HashSet::new(),
+            motion: HashMap::new(),
         }
     }
 }
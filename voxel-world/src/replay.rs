@@ -0,0 +1,362 @@
+//! Recording world sessions and playing them back.
+//!
+//! A recording is a [`Header`] followed by one [`Frame`] per tick, each
+//! encoded with postcard. A frame holds everything that reached the world
+//! from outside during its tick: commands, requests and the chunks the
+//! terrain worker finished. Replaying feeds the same input to a fresh world
+//! and compares state hashes after every tick to find where it diverged.
+
+use std::{
+    fmt,
+    io::{self, BufWriter, Read, Write},
+    sync::Arc,
+};
+
+use glam::IVec3;
+use serde::{Deserialize, Serialize};
+use voxel_core::VoxelBuffer;
+
+use crate::{
+    VoxelWorld,
+    command::WorldCommand,
+    config::WorldConfig,
+    entity::{EntityKind, Transform},
+    envelope::Envelope,
+    generator::WorldGenerator,
+    inventory::Inventory,
+    player::{GameMode, MovementMode},
+    request::{Call, PendingRequest, Ping, WorldRequest},
+    spawn,
+    terrain::Terrain,
+};
+
+/// Bumped whenever the layout of `Header` or `Frame`, or what the state
+/// hash covers, changes.
+pub const FORMAT_VERSION: u32 = 3;
+
+#[derive(Serialize, Deserialize)]
+pub struct Header {
+    pub version: u32,
+    /// Seed of the generator the session ran with. The world never sees
+    /// it; it is kept so the replay can build the same generator.
+    pub seed: u32,
+    pub config: WorldConfig,
+}
+
+/// The input of one tick and the state hash it led to.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Frame {
+    pub tick: u64,
+    /// Commands with the player they came from, `None` for the console.
+    pub commands: Vec<(Option<u32>, WorldCommand)>,
+    pub requests: Vec<WorldRequest>,
+    /// Chunks the terrain worker finished, in the order they were picked up.
+    pub generated: Vec<[i32; 3]>,
+    pub hash: u64,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Format(postcard::Error),
+    /// The file was written by a version of the world with another layout.
+    Version(u32),
+    /// Recording was asked for once the world had already run this many
+    /// ticks.
+    Started(u64),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Format(err) => write!(f, "malformed replay: {err}"),
+            Self::Version(version) => write!(
+                f,
+                "replay has format version {version}, expected {FORMAT_VERSION}"
+            ),
+            Self::Started(tick) => write!(
+                f,
+                "recording has to start with the world, which is at tick {tick}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<postcard::Error> for ReplayError {
+    fn from(err: postcard::Error) -> Self {
+        Self::Format(err)
+    }
+}
+
+/// Writes the frames of a running world.
+pub(crate) struct Recorder {
+    out: BufWriter<Box<dyn Write + Send>>,
+    frame: Frame,
+}
+
+impl Recorder {
+    pub(crate) fn begin(
+        &mut self,
+        tick: u64,
+        commands: &[Envelope<WorldCommand>],
+        requests: &[PendingRequest],
+    ) {
+        self.frame = Frame {
+            tick,
+            commands: commands
+                .iter()
+                .map(|cmd| (cmd.from, cmd.payload.clone()))
+                .collect(),
+            requests: requests
+                .iter()
+                .map(|req| match req {
+                    PendingRequest::Connect(call) => WorldRequest::Connect(call.payload.clone()),
+                    PendingRequest::Ping(_) => WorldRequest::Ping,
+                })
+                .collect(),
+            ..Frame::default()
+        };
+    }
+
+    pub(crate) fn generated(&mut self, pos: IVec3) {
+        self.frame.generated.push(pos.to_array());
+    }
+
+    /// Writes the frame begun this tick. Flushed right away so a crash
+    /// loses at most the tick it happened in.
+    fn finish(&mut self, hash: u64) -> Result<(), ReplayError> {
+        self.frame.hash = hash;
+        postcard::to_io(&self.frame, &mut self.out)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct PlayerSnapshot<'a> {
+    id: u32,
+    pos: [f32; 3],
+    velocity: [f32; 3],
+    held: bool,
+    grounded: bool,
+    game_mode: GameMode,
+    movement: MovementMode,
+    inventory: &'a Inventory,
+}
+
+#[derive(Serialize)]
+struct EntitySnapshot {
+    id: u32,
+    kind: EntityKind,
+    transform: Transform,
+    velocity: [f32; 3],
+}
+
+#[derive(Serialize)]
+struct Snapshot<'a> {
+    tick: u64,
+    time: u64,
+    players: Vec<PlayerSnapshot<'a>>,
+    entities: Vec<EntitySnapshot>,
+    terrain: u64,
+}
+
+impl VoxelWorld {
+    /// Starts writing every tick's input and state hash to `out`, so the
+    /// session can be replayed later. Has to be called before the first
+    /// tick; a replay cannot recreate what happened before it started.
+    pub fn record(
+        &mut self,
+        seed: u32,
+        out: impl Write + Send + 'static,
+    ) -> Result<(), ReplayError> {
+        if self.tick != 0 {
+            return Err(ReplayError::Started(self.tick));
+        }
+
+        let mut out = BufWriter::new(Box::new(out) as Box<dyn Write + Send>);
+        let header = Header {
+            version: FORMAT_VERSION,
+            seed,
+            config: self.config.clone(),
+        };
+        postcard::to_io(&header, &mut out)?;
+        out.flush()?;
+
+        self.recorder = Some(Recorder {
+            out,
+            frame: Frame::default(),
+        });
+        Ok(())
+    }
+
+    /// Hash of the players, entities and every terrain edit so far. Two
+    /// worlds fed the same input have the same hash after every tick.
+    pub fn state_hash(&self) -> u64 {
+        let snapshot = Snapshot {
+            tick: self.tick,
+            time: self.time(),
+            players: self
+                .players
+                .iter()
+                .map(|(&id, player)| PlayerSnapshot {
+                    id,
+                    pos: self.physics.position(player.body).to_array(),
+                    velocity: player.velocity.to_array(),
+                    held: player.held,
+                    grounded: player.grounded,
+                    game_mode: player.game_mode,
+                    movement: player.movement,
+                    inventory: &player.inventory,
+                })
+                .collect(),
+            entities: self
+                .entities
+                .iter()
+                .map(|(id, entity)| EntitySnapshot {
+                    id,
+                    kind: entity.kind(),
+                    transform: entity.transform(),
+                    velocity: entity.velocity().to_array(),
+                })
+                .collect(),
+            terrain: self.terrain_hash,
+        };
+
+        let bytes = postcard::to_stdvec(&snapshot).expect("snapshots always serialize");
+        fnv1a(&bytes)
+    }
+
+    pub(crate) fn finish_recording(&mut self) {
+        let Some(mut recorder) = self.recorder.take() else {
+            return;
+        };
+
+        match recorder.finish(self.state_hash()) {
+            Ok(()) => self.recorder = Some(recorder),
+            Err(err) => eprintln!("stopped recording at tick {}: {err}", self.tick),
+        }
+    }
+}
+
+/// Where a replay first stopped matching the recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Divergence {
+    pub tick: u64,
+    pub expected: u64,
+    pub actual: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Ticks re-simulated, up to and including a divergent one.
+    pub ticks: u64,
+    pub divergence: Option<Divergence>,
+}
+
+pub struct Replay {
+    pub header: Header,
+    pub frames: Vec<Frame>,
+}
+
+impl Replay {
+    /// Reads a recording. A frame cut off at the end, as a crash leaves
+    /// behind, is dropped.
+    pub fn read(mut input: impl Read) -> Result<Self, ReplayError> {
+        let mut bytes = Vec::new();
+        input.read_to_end(&mut bytes)?;
+
+        let (header, mut rest): (Header, _) = postcard::take_from_bytes(&bytes)?;
+        if header.version != FORMAT_VERSION {
+            return Err(ReplayError::Version(header.version));
+        }
+
+        let mut frames = Vec::new();
+        while let Ok((frame, remaining)) = postcard::take_from_bytes::<Frame>(rest) {
+            frames.push(frame);
+            rest = remaining;
+        }
+
+        Ok(Self { header, frames })
+    }
+
+    /// Re-simulates the session tick by tick and stops at the first tick
+    /// whose state hash differs from the recorded one. `generator` has to
+    /// be the one the session ran with, built from `header.seed`.
+    pub fn run(self, generator: impl WorldGenerator + 'static) -> ReplayReport {
+        let generator: Arc<dyn WorldGenerator> = Arc::new(generator);
+        let spawn = spawn::find_spawn(generator.clone(), &self.header.config.spawn);
+        let terrain = Terrain::scripted(generator);
+        let mut world = VoxelWorld::with_terrain(terrain, spawn, self.header.config);
+        let mut ticks = 0;
+
+        for frame in self.frames {
+            world
+                .terrain
+                .release(frame.generated.into_iter().map(IVec3::from_array));
+
+            let commands = frame
+                .commands
+                .into_iter()
+                .map(|(from, payload)| Envelope {
+                    to: None,
+                    from,
+                    payload,
+                })
+                .collect::<Vec<_>>();
+            let requests = frame
+                .requests
+                .into_iter()
+                .map(|req| match req {
+                    WorldRequest::Connect(connect) => PendingRequest::Connect(Call::new(connect).0),
+                    WorldRequest::Ping => PendingRequest::Ping(Call::new(Ping).0),
+                })
+                .collect::<Vec<_>>();
+
+            world.step(commands, requests);
+            ticks += 1;
+
+            let actual = world.state_hash();
+            if actual != frame.hash {
+                return ReplayReport {
+                    ticks,
+                    divergence: Some(Divergence {
+                        tick: frame.tick,
+                        expected: frame.hash,
+                        actual,
+                    }),
+                };
+            }
+        }
+
+        ReplayReport {
+            ticks,
+            divergence: None,
+        }
+    }
+}
+
+/// Folds an edited chunk into a running hash of all terrain edits, so the
+/// state hash covers the terrain without hashing every chunk each tick.
+pub(crate) fn fold_chunk(hash: u64, pos: IVec3, data: &VoxelBuffer) -> u64 {
+    let bytes =
+        postcard::to_stdvec(&(hash, pos.to_array(), data)).expect("chunks always serialize");
+    fnv1a(&bytes)
+}
+
+/// FNV-1a. Unlike `DefaultHasher` it is the same on every build, so hashes
+/// in old recordings stay comparable.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xCBF2_9CE4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+    })
+}
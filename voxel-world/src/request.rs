@@ -32,10 +32,20 @@ impl<R: Request> Call<R> {
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Connect {
     pub name: String,
+    /// Chunks around the player to stream, before the server's limit.
+    pub view_distance: i32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Accepted {
+    pub id: u32,
+    pub spawn: [f32; 3],
+    /// The largest view distance the server streams.
+    pub max_view_distance: i32,
 }
 
 impl Request for Connect {
-    type Response = u32;
+    type Response = Accepted;
 }
 
 #[derive(Serialize, Deserialize)]
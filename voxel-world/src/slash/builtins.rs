@@ -0,0 +1,310 @@
+use glam::Vec3;
+use voxel_core::Voxel;
+
+use super::{Arg, Args, CommandError, Permission, Sender, SlashCommand};
+use crate::{
+    VoxelWorld,
+    inventory::{ItemStack, MAX_STACK},
+    player::GameMode,
+    terrain::{WORLD_BORDER, WORLD_BOTTOM, WORLD_TOP},
+    time::{MIDNIGHT, NOON, SUNRISE, SUNSET},
+};
+
+/// Most items `/give` hands out at once.
+const MAX_GIVE: u32 = 64 * MAX_STACK as u32;
+
+pub fn all() -> [SlashCommand; 9] {
+    [
+        SlashCommand {
+            name: "help",
+            usage: "[command]",
+            description: "lists commands or shows how to use one",
+            permission: Permission::Everyone,
+            handler: help,
+        },
+        SlashCommand {
+            name: "list",
+            usage: "",
+            description: "lists the players online",
+            permission: Permission::Everyone,
+            handler: list,
+        },
+        SlashCommand {
+            name: "tp",
+            usage: "[player] <x> <y> <z> | [player] <target>",
+            description: "teleports a player to a position or another player",
+            permission: Permission::Operator,
+            handler: tp,
+        },
+        SlashCommand {
+            name: "spawn",
+            usage: "[player]",
+            description: "sends a player back to spawn",
+            permission: Permission::Everyone,
+            handler: spawn,
+        },
+        SlashCommand {
+            name: "give",
+            usage: "<player> <block> [count]",
+            description: "puts blocks in a player's inventory",
+            permission: Permission::Operator,
+            handler: give,
+        },
+        SlashCommand {
+            name: "gamemode",
+            usage: "<survival|creative|spectator> [player]",
+            description: "changes a player's game mode",
+            permission: Permission::Operator,
+            handler: gamemode,
+        },
+        SlashCommand {
+            name: "time",
+            usage: "[set <sunrise|noon|sunset|midnight|ticks> | add <ticks> | stop | start]",
+            description: "shows or changes the time of day",
+            permission: Permission::Everyone,
+            handler: time,
+        },
+        SlashCommand {
+            name: "stats",
+            usage: "",
+            description: "shows how long ticks take",
+            permission: Permission::Everyone,
+            handler: stats,
+        },
+        SlashCommand {
+            name: "kick",
+            usage: "<player> [reason]",
+            description: "disconnects a player",
+            permission: Permission::Operator,
+            handler: kick,
+        },
+    ]
+}
+
+/// The player named by the next argument, or the sender when there is
+/// none.
+fn player_or_sender(
+    world: &VoxelWorld,
+    sender: Sender,
+    args: &mut Args,
+) -> Result<u32, CommandError> {
+    match args.optional::<String>()? {
+        Some(name) => world.find_player(&name),
+        None => match sender {
+            Sender::Player(id) => Ok(id),
+            Sender::Console => Err(CommandError::Usage),
+        },
+    }
+}
+
+fn help(world: &mut VoxelWorld, sender: Sender, args: &mut Args) -> Result<String, CommandError> {
+    let name = args.optional::<String>()?;
+    args.finish()?;
+
+    if let Some(name) = name {
+        let name = name.trim_start_matches('/');
+        let command = world
+            .commands
+            .get(name)
+            .ok_or_else(|| CommandError::Unknown(name.to_string()))?;
+        return Ok(format!(
+            "/{} {} - {}",
+            command.name, command.usage, command.description
+        ));
+    }
+
+    let operator = world.is_operator(sender);
+    let lines: Vec<String> = world
+        .commands
+        .iter()
+        .filter(|command| operator || command.permission == Permission::Everyone)
+        .map(|command| format!("/{} {}", command.name, command.usage))
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+fn list(world: &mut VoxelWorld, _: Sender, args: &mut Args) -> Result<String, CommandError> {
+    args.finish()?;
+
+    let mut names: Vec<&str> = world.players.values().map(|p| p.name.as_str()).collect();
+    names.sort_unstable();
+    Ok(format!("{} online: {}", names.len(), names.join(", ")))
+}
+
+fn tp(world: &mut VoxelWorld, sender: Sender, args: &mut Args) -> Result<String, CommandError> {
+    // Whoever is not named moves themselves.
+    let player = match args.remaining() {
+        2 | 4 => world.find_player(&args.required::<String>()?)?,
+        1 | 3 => match sender {
+            Sender::Player(id) => id,
+            Sender::Console => return Err(CommandError::Usage),
+        },
+        _ => return Err(CommandError::Usage),
+    };
+
+    let pos = if args.remaining() == 3 {
+        let pos = Vec3::new(args.required()?, args.required()?, args.required()?);
+        if !in_world(pos) {
+            return Err(CommandError::Invalid(format!(
+                "{:.1} {:.1} {:.1} is outside the world",
+                pos.x, pos.y, pos.z
+            )));
+        }
+        pos
+    } else {
+        let target = world.find_player(&args.required::<String>()?)?;
+        world.physics.position(world.players[&target].body)
+    };
+    args.finish()?;
+
+    world.teleport_player(player, pos);
+    Ok(format!(
+        "Teleported {} to {:.1} {:.1} {:.1}",
+        world.players[&player].name, pos.x, pos.y, pos.z
+    ))
+}
+
+/// Whether `pos` lies within the world's borders and height.
+fn in_world(pos: Vec3) -> bool {
+    let border = WORLD_BORDER as f32;
+    pos.is_finite()
+        && pos.x.abs() <= border
+        && pos.z.abs() <= border
+        && (WORLD_BOTTOM as f32..=WORLD_TOP as f32).contains(&pos.y)
+}
+
+fn spawn(world: &mut VoxelWorld, sender: Sender, args: &mut Args) -> Result<String, CommandError> {
+    let player = player_or_sender(world, sender, args)?;
+    args.finish()?;
+
+    // Anyone may go back to spawn, but only operators may send others.
+    if sender != Sender::Player(player) && !world.is_operator(sender) {
+        return Err(CommandError::Denied);
+    }
+
+    world.teleport_player(player, world.spawn);
+    Ok(format!("Sent {} to spawn", world.players[&player].name))
+}
+
+fn give(world: &mut VoxelWorld, _: Sender, args: &mut Args) -> Result<String, CommandError> {
+    let player = world.find_player(&args.required::<String>()?)?;
+    let voxel: Voxel = args.required()?;
+    let count = args.optional::<u32>()?.unwrap_or(1).min(MAX_GIVE);
+    args.finish()?;
+
+    let state = world.players.get_mut(&player).unwrap();
+    let mut left = state.inventory.add(voxel, count);
+    let pos = world.physics.position(state.body);
+    let name = state.name.clone();
+
+    // Whatever does not fit lands at the player's feet.
+    while left > 0 {
+        let stack = left.min(MAX_STACK as u32);
+        world.items.spawn(
+            &mut world.physics,
+            &mut world.entities,
+            pos,
+            ItemStack {
+                voxel,
+                count: stack as u8,
+            },
+        );
+        left -= stack;
+    }
+
+    Ok(format!("Gave {count} {} to {name}", voxel.name()))
+}
+
+fn gamemode(
+    world: &mut VoxelWorld,
+    sender: Sender,
+    args: &mut Args,
+) -> Result<String, CommandError> {
+    let mode: GameMode = args.required()?;
+    let player = player_or_sender(world, sender, args)?;
+    args.finish()?;
+
+    world.set_game_mode(player, mode);
+    Ok(format!("Set {} to {mode:?}", world.players[&player].name))
+}
+
+fn time(world: &mut VoxelWorld, sender: Sender, args: &mut Args) -> Result<String, CommandError> {
+    let action = args.optional::<String>()?;
+    if action.is_some() && !world.is_operator(sender) {
+        return Err(CommandError::Denied);
+    }
+
+    let day_length = world.config.time.day_length.max(1);
+    let day_start = world.time() / day_length * day_length;
+    match action.as_deref() {
+        None => (),
+        Some("set") => {
+            let at = args.required::<String>()?;
+            let time = match at.as_str() {
+                "sunrise" => day_start + (SUNRISE * day_length as f32) as u64,
+                "noon" => day_start + (NOON * day_length as f32) as u64,
+                "sunset" => day_start + (SUNSET * day_length as f32) as u64,
+                "midnight" => day_start + (MIDNIGHT * day_length as f32) as u64,
+                ticks => u64::parse(ticks).ok_or_else(|| {
+                    CommandError::Invalid(format!("expected a time of day, got '{ticks}'"))
+                })?,
+            };
+            world.set_time(time);
+        }
+        Some("add") => {
+            let ticks: u64 = args.required()?;
+            world.set_time(world.time() + ticks);
+        }
+        Some("stop") => world.set_time_frozen(true),
+        Some("start") => world.set_time_frozen(false),
+        Some(_) => return Err(CommandError::Usage),
+    }
+    args.finish()?;
+
+    let time = world.time();
+    let of_day = time % day_length;
+    // A day is shown as 24 hours starting at 6:00, sunrise.
+    let minutes = (of_day * 24 * 60 / day_length + 6 * 60) % (24 * 60);
+    Ok(format!(
+        "Day {}, {}:{:02}{}",
+        time / day_length + 1,
+        minutes / 60,
+        minutes % 60,
+        if world.clock.is_frozen() {
+            " (stopped)"
+        } else {
+            ""
+        }
+    ))
+}
+
+fn stats(world: &mut VoxelWorld, _: Sender, args: &mut Args) -> Result<String, CommandError> {
+    args.finish()?;
+
+    let report = world.stats();
+    let mut lines = vec![format!(
+        "Tick {:.2}ms avg, {:.2}ms max of {:.1}ms; {} overruns, {} skipped",
+        report.mean_ms, report.max_ms, report.budget_ms, report.overruns, report.skipped
+    )];
+    lines.extend(report.phases.iter().map(|p| {
+        format!(
+            "  {}: {:.2}ms avg, {:.2}ms max",
+            p.phase.name(),
+            p.mean_ms,
+            p.max_ms
+        )
+    }));
+    Ok(lines.join("\n"))
+}
+
+fn kick(world: &mut VoxelWorld, _: Sender, args: &mut Args) -> Result<String, CommandError> {
+    let player = world.find_player(&args.required::<String>()?)?;
+    let reason = match args.rest() {
+        reason if reason.is_empty() => "Kicked by an operator".to_string(),
+        reason => reason,
+    };
+
+    let name = world.players[&player].name.clone();
+    world.kick(player, reason);
+    Ok(format!("Kicked {name}"))
+}
@@ -0,0 +1,206 @@
+mod builtins;
+
+use std::{collections::BTreeMap, fmt};
+
+use voxel_core::Voxel;
+
+use crate::{VoxelWorld, player::GameMode};
+
+/// Who ran a command.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sender {
+    Console,
+    Player(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    Everyone,
+    /// The console and players listed in `CommandConfig::operators`.
+    Operator,
+}
+
+/// Runs a command and returns the reply for its sender, if any.
+pub type Handler = fn(&mut VoxelWorld, Sender, &mut Args) -> Result<String, CommandError>;
+
+#[derive(Clone, Copy)]
+pub struct SlashCommand {
+    pub name: &'static str,
+    /// Arguments, as shown after the name in help.
+    pub usage: &'static str,
+    pub description: &'static str,
+    pub permission: Permission,
+    pub handler: Handler,
+}
+
+#[derive(Debug)]
+pub enum CommandError {
+    Unknown(String),
+    Denied,
+    /// The arguments did not fit the command; the reply shows its usage.
+    Usage,
+    Invalid(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown(name) => write!(f, "unknown command /{name}, try /help"),
+            Self::Denied => write!(f, "you are not allowed to do that"),
+            Self::Usage => write!(f, "wrong arguments"),
+            Self::Invalid(reason) => write!(f, "{reason}"),
+        }
+    }
+}
+
+/// A value that can be read from a single argument.
+pub trait Arg: Sized {
+    /// What the argument is, for error replies.
+    const KIND: &'static str;
+
+    fn parse(word: &str) -> Option<Self>;
+}
+
+macro_rules! from_str_arg {
+    ($($ty:ty => $kind:literal),* $(,)?) => {
+        $(impl Arg for $ty {
+            const KIND: &'static str = $kind;
+
+            fn parse(word: &str) -> Option<Self> {
+                word.parse().ok()
+            }
+        })*
+    };
+}
+
+from_str_arg! {
+    f32 => "a number",
+    i32 => "a whole number",
+    u32 => "a positive whole number",
+    u64 => "a positive whole number",
+    String => "a word",
+}
+
+impl Arg for Voxel {
+    const KIND: &'static str = "a block name";
+
+    fn parse(word: &str) -> Option<Self> {
+        Voxel::from_name(word).filter(|voxel| voxel.is_solid())
+    }
+}
+
+impl Arg for GameMode {
+    const KIND: &'static str = "survival, creative or spectator";
+
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "survival" | "s" => Some(Self::Survival),
+            "creative" | "c" => Some(Self::Creative),
+            "spectator" | "sp" => Some(Self::Spectator),
+            _ => None,
+        }
+    }
+}
+
+/// The words after a command's name, read front to back.
+pub struct Args<'a> {
+    words: Vec<&'a str>,
+    read: usize,
+}
+
+impl<'a> Args<'a> {
+    pub fn new(line: &'a str) -> Self {
+        Self {
+            words: line.split_whitespace().collect(),
+            read: 0,
+        }
+    }
+
+    /// Arguments not read yet.
+    pub fn remaining(&self) -> usize {
+        self.words.len() - self.read
+    }
+
+    pub fn required<T: Arg>(&mut self) -> Result<T, CommandError> {
+        self.optional()?.ok_or(CommandError::Usage)
+    }
+
+    pub fn optional<T: Arg>(&mut self) -> Result<Option<T>, CommandError> {
+        let Some(word) = self.words.get(self.read) else {
+            return Ok(None);
+        };
+        self.read += 1;
+
+        T::parse(word)
+            .map(Some)
+            .ok_or_else(|| CommandError::Invalid(format!("expected {}, got '{word}'", T::KIND)))
+    }
+
+    /// Everything left, joined back into one string.
+    pub fn rest(&mut self) -> String {
+        let rest = self.words[self.read..].join(" ");
+        self.read = self.words.len();
+        rest
+    }
+
+    /// Fails if any arguments were left unread.
+    pub fn finish(&self) -> Result<(), CommandError> {
+        match self.remaining() {
+            0 => Ok(()),
+            _ => Err(CommandError::Usage),
+        }
+    }
+}
+
+/// Commands players and the console can run, by name.
+pub struct Registry {
+    commands: BTreeMap<&'static str, SlashCommand>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        let mut registry = Self {
+            commands: BTreeMap::new(),
+        };
+        for command in builtins::all() {
+            registry.register(command);
+        }
+        registry
+    }
+}
+
+impl Registry {
+    /// Adds a command, replacing any with the same name.
+    pub fn register(&mut self, command: SlashCommand) {
+        self.commands.insert(command.name, command);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SlashCommand> {
+        self.commands.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SlashCommand> {
+        self.commands.values()
+    }
+}
+
+/// Parses `line`, the text after the slash, and runs it for `sender`.
+pub fn run(world: &mut VoxelWorld, sender: Sender, line: &str) -> Result<String, String> {
+    let (name, rest) = line
+        .trim()
+        .split_once(char::is_whitespace)
+        .unwrap_or((line.trim(), ""));
+
+    let Some(command) = world.commands.get(name).copied() else {
+        return Err(CommandError::Unknown(name.to_string()).to_string());
+    };
+    if command.permission == Permission::Operator && !world.is_operator(sender) {
+        return Err(CommandError::Denied.to_string());
+    }
+
+    let mut args = Args::new(rest);
+    (command.handler)(world, sender, &mut args).map_err(|e| match e {
+        CommandError::Usage => format!("usage: /{} {}", command.name, command.usage),
+        e => e.to_string(),
+    })
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use glam::{IVec2, IVec3, Vec3};
+
+use crate::{
+    PLAYER_HALF_HEIGHT,
+    config::SpawnConfig,
+    generator::WorldGenerator,
+    terrain::{Terrain, Y_RANGE, chunks_in_box, voxel_to_chunk_pos},
+};
+
+/// Used when no dry ground is found within the search radius.
+const FALLBACK_HEIGHT: f32 = 60.0;
+
+/// Finds the column nearest to the configured origin that a player can
+/// stand on, searching outward in square rings.
+pub fn find_spawn(generator: Arc<dyn WorldGenerator>, config: &SpawnConfig) -> Vec3 {
+    let origin = IVec2::from_array(config.origin);
+    // Decorations from neighboring chunks can fill a column, so candidates
+    // are checked against terrain generated the same way the world does it.
+    let mut terrain = Terrain::scripted(generator.clone());
+
+    for radius in 0..=config.search_radius {
+        for (x, z) in ring(radius) {
+            let column = origin + IVec2::new(x, z);
+            let Some(height) = generator.surface_height(column.x, column.y) else {
+                continue;
+            };
+
+            if is_clear(&mut terrain, IVec3::new(column.x, height, column.y)) {
+                return Vec3::new(
+                    column.x as f32 + 0.5,
+                    height as f32 + PLAYER_HALF_HEIGHT,
+                    column.y as f32 + 0.5,
+                );
+            }
+        }
+    }
+
+    Vec3::new(
+        origin.x as f32 + 0.5,
+        FALLBACK_HEIGHT,
+        origin.y as f32 + 0.5,
+    )
+}
+
+/// Whether a player standing with their feet in `feet` has nothing solid
+/// up to their head. Structures reach less than a chunk, so generating the
+/// chunks around the feet is enough to see everything placed there.
+fn is_clear(terrain: &mut Terrain, feet: IVec3) -> bool {
+    let (chunk, _) = voxel_to_chunk_pos(feet);
+    let missing: Vec<_> = chunks_in_box(chunk, 1)
+        .into_iter()
+        .filter(|pos| Y_RANGE.contains(&pos.y) && terrain.get(*pos).is_none())
+        .collect();
+    terrain.release(missing);
+    terrain.poll();
+
+    let height = (2.0 * PLAYER_HALF_HEIGHT).ceil() as i32;
+    (0..height).all(|dy| {
+        terrain
+            .voxel(feet + IVec3::Y * dy)
+            .is_none_or(|voxel| !voxel.is_solid())
+    })
+}
+
+/// Offsets on the border of a square with the given radius.
+fn ring(radius: i32) -> impl Iterator<Item = (i32, i32)> {
+    (-radius..=radius).flat_map(move |x| {
+        (-radius..=radius)
+            .filter(move |&z| x.abs() == radius || z.abs() == radius)
+            .map(move |z| (x, z))
+    })
+}
@@ -0,0 +1,168 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::DT;
+
+/// Ticks kept for the rolling statistics.
+const WINDOW: usize = 300;
+
+/// A group of steps in `VoxelWorld::tick`, timed together.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Commands,
+    Requests,
+    Inputs,
+    Simulation,
+    PhysicsStep,
+    Movement,
+    ChunkSync,
+    Fluids,
+    TerrainPoll,
+    ChunkStreaming,
+    Replication,
+    PhysicsChunks,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 12] = [
+        Phase::Commands,
+        Phase::Requests,
+        Phase::Inputs,
+        Phase::Simulation,
+        Phase::PhysicsStep,
+        Phase::Movement,
+        Phase::ChunkSync,
+        Phase::Fluids,
+        Phase::TerrainPoll,
+        Phase::ChunkStreaming,
+        Phase::Replication,
+        Phase::PhysicsChunks,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Commands => "commands",
+            Phase::Requests => "requests",
+            Phase::Inputs => "inputs",
+            Phase::Simulation => "simulation",
+            Phase::PhysicsStep => "physics step",
+            Phase::Movement => "movement",
+            Phase::ChunkSync => "chunk sync",
+            Phase::Fluids => "fluids",
+            Phase::TerrainPoll => "terrain poll",
+            Phase::ChunkStreaming => "chunk streaming",
+            Phase::Replication => "replication",
+            Phase::PhysicsChunks => "physics chunks",
+        }
+    }
+}
+
+/// Times the phases of one tick as they finish.
+pub struct PhaseTimer {
+    last: Instant,
+    phases: [Duration; Phase::ALL.len()],
+}
+
+impl PhaseTimer {
+    pub fn start() -> Self {
+        Self {
+            last: Instant::now(),
+            phases: Default::default(),
+        }
+    }
+
+    /// Charges the time since the previous lap to `phase`.
+    pub fn lap(&mut self, phase: Phase) {
+        let now = Instant::now();
+        self.phases[phase as usize] += now - self.last;
+        self.last = now;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct PhaseStats {
+    pub phase: Phase,
+    pub mean_ms: f32,
+    pub max_ms: f32,
+}
+
+/// Timing over the last few seconds of ticks.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatsReport {
+    pub tick: u64,
+    /// Time one tick may take without falling behind.
+    pub budget_ms: f32,
+    pub mean_ms: f32,
+    pub max_ms: f32,
+    pub phases: Vec<PhaseStats>,
+    /// Ticks that took longer than the budget, ever.
+    pub overruns: u64,
+    /// Ticks dropped because the world fell too far behind, ever.
+    pub skipped: u64,
+}
+
+impl StatsReport {
+    /// The phase that took the most time on average.
+    pub fn slowest(&self) -> Option<PhaseStats> {
+        self.phases
+            .iter()
+            .copied()
+            .max_by(|a, b| a.mean_ms.total_cmp(&b.mean_ms))
+    }
+}
+
+#[derive(Default)]
+pub struct TickStats {
+    history: VecDeque<[Duration; Phase::ALL.len()]>,
+    overruns: u64,
+    skipped: u64,
+}
+
+impl TickStats {
+    pub fn record(&mut self, timer: PhaseTimer) {
+        if self.history.len() == WINDOW {
+            self.history.pop_front();
+        }
+        self.history.push_back(timer.phases);
+    }
+
+    pub fn overrun(&mut self) {
+        self.overruns += 1;
+    }
+
+    pub fn skip(&mut self, ticks: u64) {
+        self.skipped += ticks;
+    }
+
+    pub fn report(&self, tick: u64) -> StatsReport {
+        let count = self.history.len().max(1) as f32;
+        let ms = |d: Duration| d.as_secs_f32() * 1000.0;
+
+        let phases = Phase::ALL
+            .iter()
+            .map(|&phase| {
+                let times = self.history.iter().map(|t| t[phase as usize]);
+                PhaseStats {
+                    phase,
+                    mean_ms: ms(times.clone().sum()) / count,
+                    max_ms: ms(times.max().unwrap_or_default()),
+                }
+            })
+            .collect();
+
+        let totals = self.history.iter().map(|t| t.iter().sum::<Duration>());
+        StatsReport {
+            tick,
+            budget_ms: DT * 1000.0,
+            mean_ms: ms(totals.clone().sum()) / count,
+            max_ms: ms(totals.max().unwrap_or_default()),
+            phases,
+            overruns: self.overruns,
+            skipped: self.skipped,
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Pacing of the chunks sent to each player.
+//!
+//! Chunks go out nearest first, a little sooner when they are in front of
+//! the player, and only as many per tick as the client keeps up with. Each
+//! player has a window of chunks that may be on their way at once. Acks
+//! from the client widen it; a client that stops acking has it halved.
+
+use glam::{IVec3, Vec3};
+
+use crate::config::StreamingConfig;
+
+/// Chunks straight ahead count as this much nearer, as a fraction of their
+/// distance, and chunks behind as this much farther.
+const VIEW_BIAS: f32 = 0.25;
+
+/// Flow control for the chunks sent to one player.
+pub struct ChunkStream {
+    window: u32,
+    in_flight: u32,
+    /// Tick of the last ack, or of the send that ended a quiet spell.
+    waiting_since: u64,
+}
+
+impl ChunkStream {
+    pub fn new(config: &StreamingConfig) -> Self {
+        Self {
+            window: config.initial_window,
+            in_flight: 0,
+            waiting_since: 0,
+        }
+    }
+
+    /// How many more chunks may be sent on this tick.
+    pub fn budget(&mut self, tick: u64, config: &StreamingConfig) -> usize {
+        if self.in_flight >= self.window && tick - self.waiting_since >= config.stall_ticks {
+            self.window = (self.window / 2).max(config.min_window);
+            self.waiting_since = tick;
+        }
+        let open = self.window.saturating_sub(self.in_flight) as usize;
+        open.min(config.chunks_per_tick)
+    }
+
+    pub fn sent(&mut self, count: u32, tick: u64) {
+        if self.in_flight == 0 {
+            self.waiting_since = tick;
+        }
+        self.in_flight += count;
+    }
+
+    pub fn ack(&mut self, count: u32, tick: u64, config: &StreamingConfig) {
+        self.in_flight = self.in_flight.saturating_sub(count);
+        self.window = self.window.saturating_add(count).min(config.max_window);
+        self.waiting_since = tick;
+    }
+
+    /// Chunks sent that the client has not acknowledged yet.
+    pub fn in_flight(&self) -> u32 {
+        self.in_flight
+    }
+
+    pub fn window(&self) -> u32 {
+        self.window
+    }
+}
+
+/// Sort key for a chunk waiting to be sent; lower goes first.
+pub fn priority(anchor: IVec3, look: Vec3, chunk: IVec3) -> f32 {
+    let offset = (chunk - anchor).as_vec3();
+    let distance = offset.length();
+    if distance == 0.0 {
+        return 0.0;
+    }
+    let facing = (offset / distance).dot(look.normalize_or_zero());
+    distance * (1.0 - facing * VIEW_BIAS)
+}
@@ -1,118 +1,258 @@
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     ops::RangeInclusive,
     sync::Arc,
 };
 
 use glam::{IVec3, UVec3, Vec3};
-use noise::{core::perlin::perlin_2d, permutationtable::PermutationTable};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
 use voxel_core::{Voxel, VoxelBuffer};
 
+use crate::{
+    decoration::Decorations,
+    generator::{Biome, VoxelEdit, WorldGenerator},
+};
+
 pub const CHUNK_SIZE: UVec3 = UVec3::splat(16);
 pub const CHUNK_RENDER_DISTANCE: i32 = 10;
 pub const Y_RANGE: RangeInclusive<i32> = -10..=10;
+/// Lowest voxel height the world has chunks for.
+pub const WORLD_BOTTOM: i32 = *Y_RANGE.start() * CHUNK_SIZE.y as i32;
+/// One above the highest voxel height the world has chunks for.
+pub const WORLD_TOP: i32 = (*Y_RANGE.end() + 1) * CHUNK_SIZE.y as i32;
+/// Farthest a position may be from the origin along x or z.
+pub const WORLD_BORDER: i32 = 1 << 20;
 
-struct TerrainGenerator {
-    seed_table: PermutationTable,
-}
-
-impl TerrainGenerator {
-    fn new(seed: u32) -> Self {
-        Self {
-            seed_table: PermutationTable::new(seed),
-        }
-    }
-
-    pub fn generate(&self, pos: IVec3) -> VoxelBuffer {
-        let mut buffer = VoxelBuffer::new(CHUNK_SIZE.to_array());
-
-        let scale = 0.02;
-        let height_scale = 30.0;
-        let base_height = 32.0;
-
-        for x in 0..CHUNK_SIZE.x {
-            for z in 0..CHUNK_SIZE.z {
-                let world_x = pos.x * CHUNK_SIZE.x as i32 + x as i32;
-                let world_z = pos.z * CHUNK_SIZE.z as i32 + z as i32;
-
-                let noise_value = perlin_2d(
-                    [world_x as f64 * scale, world_z as f64 * scale].into(),
-                    &self.seed_table,
-                );
-                let height = base_height + (noise_value * height_scale);
-                let height_i = height as i32;
-
-                for y in 0..CHUNK_SIZE.y {
-                    let world_y = pos.y * CHUNK_SIZE.y as i32 + y as i32;
-
-                    let voxel = if world_y < height_i - 4 {
-                        Voxel::STONE
-                    } else if world_y < height_i {
-                        Voxel::DIRT
-                    } else {
-                        Voxel::EMPTY
-                    };
-
-                    buffer.set([x, y, z], voxel);
-                }
-            }
-        }
+type Generated = (IVec3, VoxelBuffer, Vec<VoxelEdit>);
 
-        buffer
-    }
+enum Worker {
+    /// Chunks are generated on a background thread and picked up by
+    /// whichever poll comes after they are done.
+    Background {
+        request_tx: UnboundedSender<IVec3>,
+        result_rx: UnboundedReceiver<Generated>,
+    },
+    /// Chunks are generated as soon as they are requested and handed out
+    /// by the next poll, in request order.
+    Inline { ready: VecDeque<Generated> },
+    /// Requests are only noted. Chunks are generated when `release` names
+    /// them, which lets a replay hand them out on the tick they originally
+    /// arrived.
+    Scripted { ready: VecDeque<Generated> },
 }
 
 pub struct Terrain {
+    generator: Arc<dyn WorldGenerator>,
     chunks: HashMap<IVec3, Arc<VoxelBuffer>>,
     pending: HashSet<IVec3>,
-    request_tx: UnboundedSender<IVec3>,
-    result_rx: UnboundedReceiver<(IVec3, Arc<VoxelBuffer>)>,
+    decorations: Decorations,
+    modified: HashSet<IVec3>,
+    unsupported: HashSet<IVec3>,
+    worker: Worker,
 }
 
 impl Terrain {
-    pub fn new(seed: u32) -> Self {
+    pub fn new(generator: Arc<dyn WorldGenerator>) -> Self {
         let (request_tx, mut request_rx) = unbounded_channel();
         let (result_tx, result_rx) = unbounded_channel();
 
+        let worker = generator.clone();
         std::thread::spawn(move || {
-            let generator = TerrainGenerator::new(seed);
-
             while let Some(pos) = request_rx.blocking_recv() {
-                let data = Arc::new(generator.generate(pos));
-                let _ = result_tx.send((pos, data));
+                let data = worker.generate(pos);
+                let edits = worker.decorate(pos, &data);
+                let _ = result_tx.send((pos, data, edits));
             }
         });
 
+        Self::with_worker(
+            generator,
+            Worker::Background {
+                request_tx,
+                result_rx,
+            },
+        )
+    }
+
+    /// Terrain that generates chunks on the calling thread, so a chunk
+    /// requested in one tick is always ready by the next poll.
+    pub fn synchronous(generator: Arc<dyn WorldGenerator>) -> Self {
+        Self::with_worker(
+            generator,
+            Worker::Inline {
+                ready: VecDeque::new(),
+            },
+        )
+    }
+
+    /// Terrain that generates only the chunks passed to `release`.
+    pub fn scripted(generator: Arc<dyn WorldGenerator>) -> Self {
+        Self::with_worker(
+            generator,
+            Worker::Scripted {
+                ready: VecDeque::new(),
+            },
+        )
+    }
+
+    fn with_worker(generator: Arc<dyn WorldGenerator>, worker: Worker) -> Self {
         Terrain {
+            generator,
             chunks: HashMap::new(),
             pending: HashSet::new(),
-            request_tx,
-            result_rx,
+            decorations: Decorations::default(),
+            modified: HashSet::new(),
+            unsupported: HashSet::new(),
+            worker,
         }
     }
 
+    pub fn biome_at(&self, pos: Vec3) -> Option<Biome> {
+        self.generator
+            .biome_at(pos.x.floor() as i32, pos.z.floor() as i32)
+    }
+
     pub fn get(&self, pos: IVec3) -> Option<Arc<VoxelBuffer>> {
         self.chunks.get(&pos).cloned()
     }
 
+    /// The voxel at a world position, if its chunk has been generated.
+    pub fn voxel(&self, pos: IVec3) -> Option<Voxel> {
+        let (chunk, local) = voxel_to_chunk_pos(pos);
+        self.chunks
+            .get(&chunk)
+            .map(|data| data.get(local.to_array()))
+    }
+
+    /// Writes a voxel into a generated chunk. Returns whether anything
+    /// changed; writes into chunks that do not exist yet are dropped.
+    pub fn set_voxel(&mut self, pos: IVec3, voxel: Voxel) -> bool {
+        let (chunk, local) = voxel_to_chunk_pos(pos);
+        let local = local.to_array();
+
+        let Some(data) = self.chunks.get_mut(&chunk) else {
+            return false;
+        };
+        if data.get(local) == voxel {
+            return false;
+        }
+
+        Arc::make_mut(data).set(local, voxel);
+        self.decorations.forget(chunk, local);
+        self.modified.insert(chunk);
+
+        if !voxel.is_solid() {
+            self.unsupported.insert(pos + IVec3::Y);
+        }
+        if voxel.has_gravity() {
+            self.unsupported.insert(pos);
+        }
+        true
+    }
+
     pub fn request(&mut self, pos: IVec3) {
-        if !self.chunks.contains_key(&pos) && !self.pending.contains(&pos) {
-            self.pending.insert(pos);
-            let _ = self.request_tx.send(pos);
+        if self.chunks.contains_key(&pos) || !self.pending.insert(pos) {
+            return;
+        }
+
+        match &mut self.worker {
+            Worker::Background { request_tx, .. } => {
+                let _ = request_tx.send(pos);
+            }
+            Worker::Inline { ready } => {
+                let data = self.generator.generate(pos);
+                let edits = self.generator.decorate(pos, &data);
+                ready.push_back((pos, data, edits));
+            }
+            Worker::Scripted { .. } => {}
         }
     }
 
+    /// Generates the given chunks for the next poll to hand out, in order.
+    /// Does nothing unless the terrain is scripted.
+    pub fn release(&mut self, positions: impl IntoIterator<Item = IVec3>) {
+        let Worker::Scripted { ready } = &mut self.worker else {
+            return;
+        };
+
+        for pos in positions {
+            let data = self.generator.generate(pos);
+            let edits = self.generator.decorate(pos, &data);
+            ready.push_back((pos, data, edits));
+        }
+    }
+
+    /// Collects newly generated chunks. Their decorations are applied
+    /// before they are returned; chunks that were already generated and got
+    /// written into are reported by `take_modified`.
     pub fn poll(&mut self) -> Vec<(IVec3, Arc<VoxelBuffer>)> {
         let mut ready = Vec::new();
 
-        while let Ok((pos, data)) = self.result_rx.try_recv() {
-            self.chunks.insert(pos, data.clone());
+        while let Some((pos, mut data, edits)) = self.next_generated() {
+            self.decorations.apply_deferred(pos, &mut data);
+            self.chunks.insert(pos, Arc::new(data));
             self.pending.remove(&pos);
-            ready.push((pos, data));
+            self.decorate(edits);
+            ready.push(pos);
         }
 
         ready
+            .into_iter()
+            .map(|pos| {
+                self.modified.remove(&pos);
+                (pos, self.chunks[&pos].clone())
+            })
+            .collect()
+    }
+
+    fn next_generated(&mut self) -> Option<Generated> {
+        match &mut self.worker {
+            Worker::Background { result_rx, .. } => result_rx.try_recv().ok(),
+            Worker::Inline { ready } | Worker::Scripted { ready } => ready.pop_front(),
+        }
+    }
+
+    pub fn take_modified(&mut self) -> Vec<(IVec3, Arc<VoxelBuffer>)> {
+        self.modified
+            .drain()
+            .map(|pos| (pos, self.chunks[&pos].clone()))
+            .collect()
+    }
+
+    /// Gravity-affected voxels that may have lost the voxel under them
+    /// since the last call.
+    pub fn take_unsupported(&mut self) -> Vec<IVec3> {
+        let mut unsupported: Vec<_> = std::mem::take(&mut self.unsupported)
+            .into_iter()
+            .filter(|&pos| {
+                self.voxel(pos).is_some_and(|v| v.has_gravity())
+                    && self.voxel(pos - IVec3::Y).is_some_and(|v| !v.is_solid())
+            })
+            .collect();
+        unsupported.sort_unstable_by_key(|pos| (pos.y, pos.x, pos.z));
+        unsupported
+    }
+
+    /// Hands positions from `take_unsupported` back to the next call, for
+    /// blocks that can't fall yet.
+    pub fn keep_unsupported(&mut self, positions: impl IntoIterator<Item = IVec3>) {
+        self.unsupported.extend(positions);
+    }
+
+    fn decorate(&mut self, edits: Vec<VoxelEdit>) {
+        for edit in edits {
+            let (chunk, local) = voxel_to_chunk_pos(edit.pos);
+            let local = local.to_array();
+
+            match self.chunks.get_mut(&chunk) {
+                Some(data) => {
+                    if self.decorations.write(chunk, local, edit.voxel, data) {
+                        self.modified.insert(chunk);
+                    }
+                }
+                None => self.decorations.defer(chunk, local, edit.voxel),
+            }
+        }
     }
 }
 
@@ -124,6 +264,11 @@ pub fn world_to_chunk_pos(pos: Vec3) -> IVec3 {
     )
 }
 
+pub fn voxel_to_chunk_pos(pos: IVec3) -> (IVec3, UVec3) {
+    let size = CHUNK_SIZE.as_ivec3();
+    (pos.div_euclid(size), pos.rem_euclid(size).as_uvec3())
+}
+
 pub fn chunk_in_range(anchor: IVec3, chunk_pos: IVec3, radius: i32) -> bool {
     let diff = chunk_pos - anchor;
     diff.x.abs() <= radius && diff.z.abs() <= radius && Y_RANGE.contains(&chunk_pos.y)
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+/// Named points in the day, as fractions of the day length.
+pub const SUNRISE: f32 = 0.0;
+pub const NOON: f32 = 0.25;
+pub const SUNSET: f32 = 0.5;
+pub const MIDNIGHT: f32 = 0.75;
+
+/// World time, which advances with the world tick unless frozen. Time 0
+/// is sunrise on the first day.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct WorldClock {
+    /// Added to the tick to get the time while running.
+    offset: i64,
+    /// The time the clock stopped at, if it is frozen.
+    frozen: Option<u64>,
+}
+
+impl WorldClock {
+    pub fn time(&self, tick: u64) -> u64 {
+        self.frozen
+            .unwrap_or_else(|| (tick as i64 + self.offset).max(0) as u64)
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
+    pub fn set(&mut self, tick: u64, time: u64) {
+        match &mut self.frozen {
+            Some(frozen) => *frozen = time,
+            None => self.offset = time as i64 - tick as i64,
+        }
+    }
+
+    pub fn freeze(&mut self, tick: u64) {
+        self.frozen = Some(self.time(tick));
+    }
+
+    pub fn resume(&mut self, tick: u64) {
+        if let Some(time) = self.frozen.take() {
+            self.offset = time as i64 - tick as i64;
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Helpers shared by the integration tests. Each test crate uses a different
+//! subset of them.
+#![allow(dead_code)]
+
+use glam::IVec3;
+use voxel_world::{
+    config::{StreamingConfig, WorldConfig},
+    event::WorldEvent,
+    generator::FlatGenerator,
+    headless::Headless,
+};
+
+/// View distance for tests that don't look at the edge of the view, which
+/// keeps joining from generating thousands of chunks.
+pub const VIEW_DISTANCE: i32 = 2;
+
+/// The default config with the view distance capped at [`VIEW_DISTANCE`].
+pub fn small_view() -> WorldConfig {
+    WorldConfig {
+        streaming: StreamingConfig {
+            max_view_distance: VIEW_DISTANCE,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Sends every chunk as soon as it is ready, so tests see whole views load in
+/// one tick.
+pub fn unpaced(view_distance: i32) -> WorldConfig {
+    WorldConfig {
+        streaming: StreamingConfig {
+            max_view_distance: view_distance,
+            chunks_per_tick: usize::MAX,
+            initial_window: u32::MAX,
+            max_window: u32::MAX,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// A flat, unpaced world.
+pub fn world_with_view(view_distance: i32) -> Headless {
+    Headless::new(FlatGenerator::default(), unpaced(view_distance))
+}
+
+pub fn loaded_chunks(world: &Headless, id: u32) -> Vec<IVec3> {
+    world
+        .events_for(id)
+        .filter_map(|event| match event {
+            WorldEvent::ChunkLoaded(loaded) => Some(IVec3::from_array(loaded.pos)),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn unloaded_chunks(world: &Headless, id: u32) -> Vec<IVec3> {
+    world
+        .events_for(id)
+        .filter_map(|event| match event {
+            WorldEvent::ChunkUnloaded(unloaded) => Some(IVec3::from_array(unloaded.pos)),
+            _ => None,
+        })
+        .collect()
+}
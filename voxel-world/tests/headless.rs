@@ -0,0 +1,334 @@
+mod common;
+
+use common::{VIEW_DISTANCE, loaded_chunks, small_view, world_with_view};
+use glam::{IVec3, Vec3};
+use voxel_core::{Voxel, VoxelBuffer};
+use voxel_world::{
+    PHYSICS_RADIUS,
+    command::{BreakBlock, Chat, PlaceBlock, WorldCommand},
+    envelope::CONSOLE,
+    event::WorldEvent,
+    generator::{FlatGenerator, VoxelEdit, WorldGenerator},
+    headless::Headless,
+    inventory::CREATIVE_PALETTE,
+    player::MovementMode,
+    terrain::{CHUNK_RENDER_DISTANCE, CHUNK_SIZE, Y_RANGE, voxel_to_chunk_pos, world_to_chunk_pos},
+};
+
+fn world() -> Headless {
+    world_with_view(VIEW_DISTANCE)
+}
+
+#[test]
+fn join_and_leave_are_broadcast() {
+    let mut world = world();
+    let alice = world.connect("alice");
+
+    assert_eq!(world.world().player_ids(), vec![alice.id]);
+    assert!(
+        world.events().iter().any(|e| e.to.is_none()
+            && matches!(&e.payload, WorldEvent::PlayerJoined(j) if j.id == alice.id))
+    );
+
+    world.send(alice.id, WorldCommand::Disconnect);
+    world.step();
+
+    assert!(world.world().player_ids().is_empty());
+    assert!(
+        world
+            .events()
+            .iter()
+            .any(|e| matches!(&e.payload, WorldEvent::PlayerLeft(l) if l.id == alice.id))
+    );
+}
+
+#[test]
+fn new_players_learn_about_existing_ones() {
+    let mut world = world();
+    let alice = world.connect("alice");
+    world.take_events();
+
+    let bob = world.connect("bob");
+
+    assert!(world.events().iter().any(|e| e.to == Some(bob.id)
+        && matches!(&e.payload, WorldEvent::PlayerJoined(j) if j.id == alice.id)));
+}
+
+#[test]
+fn chunks_around_the_player_load_on_join() {
+    let mut world = world_with_view(CHUNK_RENDER_DISTANCE);
+    let alice = world.connect("alice");
+
+    let anchor = world_to_chunk_pos(Vec3::from_array(alice.spawn));
+    let loaded = loaded_chunks(&world, alice.id);
+
+    assert!(loaded.contains(&anchor));
+    assert!(loaded.contains(&IVec3::new(
+        anchor.x + CHUNK_RENDER_DISTANCE,
+        *Y_RANGE.start(),
+        anchor.z - CHUNK_RENDER_DISTANCE,
+    )));
+    assert!(world.world().chunk(anchor).is_some());
+}
+
+#[test]
+fn teleporting_moves_chunk_interest() {
+    let mut world = world();
+    let alice = world.connect("alice");
+    world.step();
+    world.take_events();
+
+    let far = 16 * (2 * CHUNK_RENDER_DISTANCE + 1);
+    world.console(Chat {
+        text: format!("tp alice {far} 40 0"),
+    });
+    world.step_n(2);
+
+    let old = world_to_chunk_pos(Vec3::from_array(alice.spawn));
+    let new = world_to_chunk_pos(Vec3::new(far as f32, 40.0, 0.0));
+
+    assert!(world.events_for(alice.id).any(|event| matches!(
+        event,
+        WorldEvent::ChunkUnloaded(unloaded) if IVec3::from_array(unloaded.pos) == old
+    )));
+    assert!(loaded_chunks(&world, alice.id).contains(&new));
+}
+
+#[test]
+fn teleports_outside_the_world_are_refused() {
+    let mut world = world();
+    let alice = world.connect("alice");
+    let spawn = Vec3::from_array(alice.spawn);
+
+    for coords in ["nan 40 0", "inf 40 0", "0 40 1e30", "0 1e9 0"] {
+        world.take_events();
+        world.console(Chat {
+            text: format!("tp alice {coords}"),
+        });
+        world.step();
+
+        assert!(world.events().iter().any(|e| e.to == Some(CONSOLE)
+            && matches!(&e.payload, WorldEvent::CommandReply(reply) if reply.error)));
+        let pos = world.world().player_position(alice.id).unwrap();
+        assert!(pos.distance(spawn) < 2.0, "moved to {pos}");
+    }
+}
+
+#[test]
+fn creative_players_fly_and_place_without_items() {
+    let mut world = world();
+    let alice = world.connect("alice");
+    world.console(Chat {
+        text: "gamemode creative alice".to_string(),
+    });
+    world.step();
+    let player = world.world().player(alice.id).unwrap();
+    assert_eq!(player.movement, MovementMode::Fly);
+    assert_eq!(player.inventory.selected_item(), None);
+
+    let pos = Vec3::from_array(alice.spawn).floor().as_ivec3() + IVec3::new(3, 0, 0);
+    world.send(
+        alice.id,
+        PlaceBlock {
+            pos: pos.to_array(),
+        },
+    );
+    world.step();
+    assert_eq!(world.world().voxel(pos), Some(CREATIVE_PALETTE[0]));
+}
+
+#[test]
+fn physics_chunks_follow_players() {
+    let mut world = world();
+    let alice = world.connect("alice");
+    world.step();
+
+    // The spawn chunk is air; the ground is in the one below.
+    let chunk = world_to_chunk_pos(Vec3::from_array(alice.spawn)) - IVec3::Y;
+    let edge = chunk + IVec3::new(PHYSICS_RADIUS, 0, PHYSICS_RADIUS);
+    assert!(world.world().has_physics_chunk(chunk));
+    assert!(world.world().has_physics_chunk(edge));
+    assert!(!world.world().has_physics_chunk(edge + IVec3::X));
+
+    world.send(alice.id, WorldCommand::Disconnect);
+    world.step();
+
+    assert!(!world.world().has_physics_chunk(chunk));
+}
+
+#[test]
+fn held_players_are_released_onto_the_ground() {
+    let mut world = world();
+    let alice = world.connect("alice");
+    world.step_n(60);
+
+    let player = world.world().player(alice.id).unwrap();
+    assert!(!player.held);
+    assert!(player.grounded);
+
+    let pos = world.world().player_position(alice.id).unwrap();
+    assert!((pos.y - alice.spawn[1]).abs() < 0.1);
+}
+
+#[test]
+fn idle_grounded_players_stay_put() {
+    let mut world = world();
+    let alice = world.connect("alice");
+    world.step_n(60);
+    let settled = world.world().player_position(alice.id).unwrap();
+
+    world.step_n(300);
+    assert!(world.world().player(alice.id).unwrap().grounded);
+    assert_eq!(world.world().player_position(alice.id), Some(settled));
+}
+
+#[test]
+fn identical_input_gives_identical_worlds() {
+    let run = || {
+        let mut world = world();
+        let alice = world.connect("alice");
+        world.step_n(30);
+        world.console(Chat {
+            text: "tp alice 3 50 -7".to_string(),
+        });
+        world.step_n(60);
+        (
+            world.world().tick_count(),
+            world.world().player_position(alice.id),
+        )
+    };
+
+    assert_eq!(run(), run());
+}
+
+#[test]
+fn movement_is_only_sent_for_players_in_view() {
+    let mut world = world();
+    let alice = world.connect("alice");
+    let bob = world.connect("bob");
+    world.step();
+
+    let entered = |world: &Headless, viewer: u32, id: u32| {
+        world.events().iter().any(|e| {
+            e.to == Some(viewer)
+                && matches!(&e.payload, WorldEvent::PlayerEnteredView(v) if v.id == id)
+        })
+    };
+    assert!(entered(&world, alice.id, bob.id));
+    assert!(entered(&world, bob.id, alice.id));
+
+    let far = 16 * (2 * CHUNK_RENDER_DISTANCE + 1);
+    world.console(Chat {
+        text: format!("tp bob {far} 40 0"),
+    });
+    world.step_n(2);
+    assert!(world.events().iter().any(|e| e.to == Some(alice.id)
+        && matches!(&e.payload, WorldEvent::PlayerExitedView(v) if v.id == bob.id)));
+
+    world.take_events();
+    world.step();
+    assert!(!world.events().iter().any(|e| e.to == Some(alice.id)
+        && matches!(&e.payload, WorldEvent::PlayerMoved(m) if m.id == bob.id)));
+    assert!(world.events().iter().any(|e| e.to == Some(alice.id)
+        && matches!(&e.payload, WorldEvent::PlayerMoved(m) if m.id == alice.id)));
+}
+
+/// Flat ground with a trunk over the origin column, placed by the chunk to
+/// its west the way a tree reaches across a chunk border.
+struct TrunkAtOrigin(FlatGenerator);
+
+impl WorldGenerator for TrunkAtOrigin {
+    fn generate(&self, pos: IVec3) -> VoxelBuffer {
+        self.0.generate(pos)
+    }
+
+    fn decorate(&self, pos: IVec3, _chunk: &VoxelBuffer) -> Vec<VoxelEdit> {
+        let ground = self.0.surface_height(0, 0).unwrap();
+        if pos != world_to_chunk_pos(Vec3::new(-1.0, ground as f32, 0.0)) {
+            return Vec::new();
+        }
+
+        (ground..ground + 6)
+            .map(|y| VoxelEdit {
+                pos: IVec3::new(0, y, 0),
+                voxel: Voxel::LOG,
+            })
+            .collect()
+    }
+
+    fn surface_height(&self, world_x: i32, world_z: i32) -> Option<i32> {
+        self.0.surface_height(world_x, world_z)
+    }
+}
+
+#[test]
+fn players_do_not_spawn_inside_decorations() {
+    let mut world = Headless::new(TrunkAtOrigin(FlatGenerator::default()), small_view());
+    let alice = world.connect("alice");
+
+    let spawn = Vec3::from_array(alice.spawn);
+    assert_ne!((spawn.x.floor(), spawn.z.floor()), (0.0, 0.0));
+    assert!(spawn.x.abs() < 2.0 && spawn.z.abs() < 2.0);
+}
+
+/// Flat ground with a sand pillar next to the origin, tall enough to reach
+/// past the physics chunks of a player standing beside it.
+struct SandPillar(FlatGenerator);
+
+impl SandPillar {
+    const COLUMN: (i32, i32) = (3, 0);
+    const HEIGHT: i32 = 4 * CHUNK_SIZE.y as i32;
+}
+
+impl WorldGenerator for SandPillar {
+    fn generate(&self, pos: IVec3) -> VoxelBuffer {
+        let mut buffer = self.0.generate(pos);
+        let ground = self.0.surface_height(0, 0).unwrap();
+        let (x, z) = Self::COLUMN;
+        for y in ground..ground + Self::HEIGHT {
+            let (chunk, local) = voxel_to_chunk_pos(IVec3::new(x, y, z));
+            if chunk == pos {
+                buffer.set(local.to_array(), Voxel::SAND);
+            }
+        }
+        buffer
+    }
+
+    fn surface_height(&self, world_x: i32, world_z: i32) -> Option<i32> {
+        self.0.surface_height(world_x, world_z)
+    }
+}
+
+#[test]
+fn blocks_above_the_physics_chunks_fall_once_they_get_colliders() {
+    let generator = SandPillar(FlatGenerator::default());
+    let ground = generator.surface_height(0, 0).unwrap();
+    let (x, z) = SandPillar::COLUMN;
+    let top = IVec3::new(x, ground + SandPillar::HEIGHT - 1, z);
+
+    let mut world = Headless::new(generator, small_view());
+    let alice = world.connect("alice");
+    world.console(Chat {
+        text: "gamemode creative alice".to_string(),
+    });
+    world.step_n(30);
+    world.send(
+        alice.id,
+        BreakBlock {
+            pos: [x, ground, z],
+        },
+    );
+    world.step_n(300);
+
+    // The top of the pillar sits over a chunk without colliders, so it
+    // waits there instead of falling forever.
+    let (chunk, _) = voxel_to_chunk_pos(top);
+    assert!(!world.world().has_physics_chunk(chunk));
+    assert_eq!(world.world().voxel(top), Some(Voxel::SAND));
+
+    world.console(Chat {
+        text: format!("tp alice 0.5 {} 0.5", top.y + 1),
+    });
+    world.step_n(300);
+    assert_eq!(world.world().voxel(top), Some(Voxel::EMPTY));
+}
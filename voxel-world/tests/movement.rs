@@ -0,0 +1,121 @@
+mod common;
+
+use common::small_view;
+use glam::Vec3;
+use voxel_world::{
+    command::AckMovement,
+    event::WorldEvent,
+    generator::FlatGenerator,
+    headless::Headless,
+    movement::{KEYFRAME_INTERVAL, Motion, MotionHistory, MotionState, MotionSync},
+};
+
+fn state(x: f32) -> MotionState {
+    MotionState::new(Vec3::new(x, 40.0, -3.25), Vec3::new(0.0, 0.0, -1.0))
+}
+
+#[test]
+fn quantized_states_round_trip_closely() {
+    let pos = Vec3::new(123.456, -7.89, 0.001);
+    let look = Vec3::new(1.0, 2.0, -0.5).normalize();
+    let state = MotionState::new(pos, look);
+
+    assert!(state.pos().distance(pos) < 0.01);
+    assert!(state.look().distance(look) < 0.001);
+}
+
+#[test]
+fn deltas_decode_against_the_acknowledged_state() {
+    let mut sync = MotionSync::default();
+    let mut history = MotionHistory::default();
+
+    let first = sync.encode(1, state(0.0)).unwrap();
+    assert!(matches!(first, Motion::Keyframe(_)));
+    assert_eq!(history.receive(1, first), Some(state(0.0)));
+    sync.ack(1);
+
+    // The datagram for tick 2 is lost; tick 3 still decodes.
+    sync.encode(2, state(0.5)).unwrap();
+    let third = sync.encode(3, state(1.0)).unwrap();
+    assert!(matches!(third, Motion::Delta { base: 1, .. }));
+    assert_eq!(history.receive(3, third), Some(state(1.0)));
+}
+
+#[test]
+fn acknowledged_idle_states_are_not_resent() {
+    let mut sync = MotionSync::default();
+
+    assert!(sync.encode(1, state(2.0)).is_some());
+    assert!(sync.encode(2, state(2.0)).is_some());
+    sync.ack(2);
+    assert_eq!(sync.encode(3, state(2.0)), None);
+    assert!(sync.encode(4, state(3.0)).is_some());
+}
+
+#[test]
+fn returning_to_the_acknowledged_state_is_still_sent() {
+    let mut sync = MotionSync::default();
+    sync.encode(1, state(0.0));
+    sync.ack(1);
+
+    // The client may be showing the state from tick 2 if its ack was lost.
+    assert!(sync.encode(2, state(1.0)).is_some());
+    assert!(sync.encode(3, state(0.0)).is_some());
+    assert_eq!(sync.encode(4, state(0.0)), None);
+}
+
+#[test]
+fn old_bases_fall_back_to_keyframes() {
+    let mut sync = MotionSync::default();
+    sync.encode(1, state(0.0));
+    sync.ack(1);
+
+    let late = sync.encode(1 + KEYFRAME_INTERVAL, state(1.0)).unwrap();
+    assert!(matches!(late, Motion::Keyframe(_)));
+
+    let far = sync.encode(2 + KEYFRAME_INTERVAL, state(1000.0)).unwrap();
+    assert!(matches!(far, Motion::Keyframe(_)));
+}
+
+#[test]
+fn idle_players_stop_costing_bandwidth() {
+    let mut world = Headless::new(FlatGenerator::default(), small_view());
+    let alice = world.connect("alice");
+
+    // Let alice land before acking, so there is nothing left to send.
+    let mut last = world.world().player_position(alice.id);
+    for _ in 0..200 {
+        world.step();
+        let position = world.world().player_position(alice.id);
+        if position == last && world.world().player(alice.id).unwrap().grounded {
+            break;
+        }
+        last = position;
+    }
+    world.step();
+    assert_eq!(world.world().player_position(alice.id), last);
+
+    let moved = |world: &Headless| {
+        world
+            .events()
+            .iter()
+            .filter_map(|e| match &e.payload {
+                WorldEvent::PlayerMoved(moved) if e.to == Some(alice.id) => Some(moved.tick),
+                _ => None,
+            })
+            .max()
+    };
+
+    let tick = moved(&world).unwrap();
+    world.send(
+        alice.id,
+        AckMovement {
+            acks: vec![(alice.id, tick)],
+        },
+    );
+    world.step();
+    world.take_events();
+
+    world.step_n(10);
+    assert_eq!(moved(&world), None);
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use std::{
+    io::{self, Write},
+    sync::{Arc, Mutex},
+};
+
+use common::small_view;
+use voxel_world::{
+    command::{BreakBlock, Chat, MovePlayer},
+    generator::FlatGenerator,
+    headless::Headless,
+    player::PlayerInput,
+    replay::{Replay, ReplayError},
+};
+
+/// A writer the test can read back after handing it to the world.
+#[derive(Clone, Default)]
+struct Shared(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Shared {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn record_session() -> Vec<u8> {
+    let out = Shared::default();
+    let mut world = Headless::new(FlatGenerator::default(), small_view());
+    world.world_mut().record(7, out.clone()).unwrap();
+
+    let alice = world.connect("alice");
+    world.step_n(10);
+    world.send(
+        alice.id,
+        MovePlayer {
+            input: PlayerInput {
+                dir: [1.0, 0.0, 0.0],
+                ..Default::default()
+            },
+        },
+    );
+    world.step_n(20);
+    world.console(Chat {
+        text: "give alice stone 5".to_string(),
+    });
+    world.step_n(5);
+
+    out.0.lock().unwrap().clone()
+}
+
+#[test]
+fn replay_matches_the_recording() {
+    let bytes = record_session();
+    let replay = Replay::read(bytes.as_slice()).unwrap();
+
+    assert_eq!(replay.header.seed, 7);
+    assert_eq!(replay.frames.len(), 36);
+
+    let report = replay.run(FlatGenerator::default());
+    assert_eq!(report.ticks, 36);
+    assert_eq!(report.divergence, None);
+}
+
+#[test]
+fn replay_reports_the_first_divergent_tick() {
+    let bytes = record_session();
+    let mut replay = Replay::read(bytes.as_slice()).unwrap();
+    replay.frames[20].hash ^= 1;
+    let tick = replay.frames[20].tick;
+
+    let report = replay.run(FlatGenerator::default());
+    let divergence = report.divergence.unwrap();
+    assert_eq!(divergence.tick, tick);
+    assert_eq!(report.ticks, 21);
+}
+
+#[test]
+fn truncated_recordings_keep_whole_frames() {
+    let mut bytes = record_session();
+    bytes.pop();
+
+    let replay = Replay::read(bytes.as_slice()).unwrap();
+    assert_eq!(replay.frames.len(), 35);
+}
+
+#[test]
+fn recording_has_to_start_with_the_world() {
+    let mut world = Headless::new(FlatGenerator::default(), small_view());
+    world.step();
+
+    let result = world.world_mut().record(7, Shared::default());
+    assert!(matches!(result, Err(ReplayError::Started(1))));
+}
+
+#[test]
+fn terrain_edits_change_the_state_hash() {
+    let hash_after = |edit: bool| {
+        let mut world = Headless::new(FlatGenerator::default(), small_view());
+        let alice = world.connect("alice");
+        world.console(Chat {
+            text: "gamemode creative alice".to_string(),
+        });
+        world.step();
+
+        // Off to the side of the player, so only the terrain differs.
+        if edit {
+            let spawn = alice.spawn.map(f32::floor);
+            let pos = [spawn[0] as i32 + 3, 31, spawn[2] as i32];
+            world.send(alice.id, BreakBlock { pos });
+        }
+        world.step();
+        world.world().state_hash()
+    };
+
+    assert_ne!(hash_after(false), hash_after(true));
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use common::{VIEW_DISTANCE, loaded_chunks, small_view, unloaded_chunks, unpaced};
+use glam::{IVec3, Vec3};
+use voxel_world::{
+    command::{AckChunks, SetViewDistance},
+    config::StreamingConfig,
+    generator::FlatGenerator,
+    headless::Headless,
+    streaming::{ChunkStream, priority},
+    terrain::{CHUNK_RENDER_DISTANCE, chunk_in_range, world_to_chunk_pos},
+};
+
+#[test]
+fn chunks_ahead_go_before_chunks_behind() {
+    let anchor = IVec3::ZERO;
+    let look = Vec3::X;
+
+    assert_eq!(priority(anchor, look, anchor), 0.0);
+    assert!(priority(anchor, look, IVec3::X) < priority(anchor, look, -IVec3::X));
+    assert!(priority(anchor, look, -IVec3::X) < priority(anchor, look, 2 * IVec3::X));
+}
+
+#[test]
+fn acks_widen_the_window() {
+    let config = StreamingConfig::default();
+    let mut stream = ChunkStream::new(&config);
+    let window = stream.window();
+
+    stream.sent(10, 1);
+    assert_eq!(stream.in_flight(), 10);
+    stream.ack(10, 2, &config);
+    assert_eq!(stream.in_flight(), 0);
+    assert_eq!(stream.window(), window + 10);
+}
+
+#[test]
+fn stalled_windows_are_halved() {
+    let config = StreamingConfig::default();
+    let mut stream = ChunkStream::new(&config);
+    let window = stream.window();
+
+    stream.sent(window, 1);
+    assert_eq!(stream.budget(2, &config), 0);
+    assert_eq!(stream.window(), window);
+
+    stream.budget(1 + config.stall_ticks, &config);
+    assert_eq!(stream.window(), window / 2);
+
+    for stall in 2..16 {
+        stream.budget(1 + stall * config.stall_ticks, &config);
+    }
+    assert_eq!(stream.window(), config.min_window);
+}
+
+#[test]
+fn joining_players_get_the_nearest_chunks_first() {
+    let config = small_view();
+    let per_tick = config.streaming.chunks_per_tick;
+    let mut world = Headless::new(FlatGenerator::default(), config);
+    let alice = world.connect("alice");
+
+    let anchor = world_to_chunk_pos(Vec3::from_array(alice.spawn));
+    let loaded = loaded_chunks(&world, alice.id);
+    assert_eq!(loaded.len(), per_tick);
+    assert_eq!(loaded[0], anchor);
+    assert!(
+        loaded
+            .windows(2)
+            .all(|pair| pair[0].distance_squared(anchor) <= pair[1].distance_squared(anchor))
+    );
+}
+
+#[test]
+fn streaming_stops_at_the_window_until_acked() {
+    let config = small_view();
+    let window = config.streaming.initial_window as usize;
+    let mut world = Headless::new(FlatGenerator::default(), config);
+    let alice = world.connect("alice");
+    world.step_n(10);
+    assert_eq!(loaded_chunks(&world, alice.id).len(), window);
+
+    world.send(alice.id, AckChunks { count: 16 });
+    world.take_events();
+    world.step_n(10);
+    assert_eq!(loaded_chunks(&world, alice.id).len(), 32);
+}
+
+#[test]
+fn view_distance_changes_stream_only_the_difference() {
+    let mut world = Headless::new(FlatGenerator::default(), unpaced(VIEW_DISTANCE));
+    let alice = world.connect("alice");
+    let anchor = world_to_chunk_pos(Vec3::from_array(alice.spawn));
+
+    world.send(alice.id, SetViewDistance { distance: 1 });
+    world.take_events();
+    world.step();
+    let unloaded = unloaded_chunks(&world, alice.id);
+    assert!(!unloaded.is_empty());
+    assert!(unloaded.iter().all(|&pos| !chunk_in_range(anchor, pos, 1)));
+    assert!(loaded_chunks(&world, alice.id).is_empty());
+
+    world.send(alice.id, SetViewDistance { distance: 2 });
+    world.take_events();
+    world.step();
+    let loaded = loaded_chunks(&world, alice.id);
+    assert!(!loaded.is_empty());
+    assert!(
+        loaded
+            .iter()
+            .all(|&pos| chunk_in_range(anchor, pos, 2) && !chunk_in_range(anchor, pos, 1))
+    );
+    assert!(unloaded_chunks(&world, alice.id).is_empty());
+}
+
+#[test]
+fn view_distance_is_clamped_to_the_server_maximum() {
+    let mut world = Headless::new(FlatGenerator::default(), unpaced(2));
+    let alice = world.connect("alice");
+    let anchor = world_to_chunk_pos(Vec3::from_array(alice.spawn));
+    assert!(
+        loaded_chunks(&world, alice.id)
+            .iter()
+            .all(|&pos| chunk_in_range(anchor, pos, 2))
+    );
+
+    world.send(
+        alice.id,
+        SetViewDistance {
+            distance: CHUNK_RENDER_DISTANCE * 2,
+        },
+    );
+    world.take_events();
+    world.step();
+    assert!(loaded_chunks(&world, alice.id).is_empty());
+}
+
+#[test]
+fn joining_players_stream_the_view_distance_they_ask_for() {
+    let mut world = Headless::new(FlatGenerator::default(), unpaced(VIEW_DISTANCE));
+    let alice = world.connect_with_view("alice", 1);
+    assert_eq!(alice.max_view_distance, VIEW_DISTANCE);
+
+    let anchor = world_to_chunk_pos(Vec3::from_array(alice.spawn));
+    let loaded = loaded_chunks(&world, alice.id);
+    assert!(!loaded.is_empty());
+    assert!(loaded.iter().all(|&pos| chunk_in_range(anchor, pos, 1)));
+
+    // Nothing is unloaded or resent once the preference is confirmed.
+    world.send(alice.id, SetViewDistance { distance: 1 });
+    world.take_events();
+    world.step();
+    assert!(loaded_chunks(&world, alice.id).is_empty());
+    assert!(unloaded_chunks(&world, alice.id).is_empty());
+}